@@ -0,0 +1,88 @@
+//! Demonstrates the contention difference between the reserved-slot pool
+//! (`gmodx::lua::bench_support`, requires the `internal-benchmarks` feature) and the single
+//! `Mutex<FxHashMap<i32, bool>>` free list it replaced, under a bursty acquire/release workload
+//! (many short-lived table/value refs created and dropped concurrently from several threads).
+//!
+//! Run with: `cargo bench --bench value_ref_contention --features internal-benchmarks`
+
+use std::{collections::HashMap, sync::Mutex, thread};
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use gmodx::lua::bench_support;
+
+/// Stand-in for the pre-redesign allocator: every acquire/release takes the one global lock.
+struct LegacyFreeList {
+    free: Mutex<HashMap<i32, bool>>,
+    next: Mutex<i32>,
+}
+
+impl LegacyFreeList {
+    fn new() -> Self {
+        Self {
+            free: Mutex::new(HashMap::new()),
+            next: Mutex::new(0),
+        }
+    }
+
+    fn acquire(&self) -> i32 {
+        let free = {
+            let mut free = self.free.lock().unwrap();
+            let key = free.keys().next().copied();
+            if let Some(key) = key {
+                free.remove(&key);
+            }
+            key
+        };
+        free.unwrap_or_else(|| {
+            let mut next = self.next.lock().unwrap();
+            *next += 1;
+            *next
+        })
+    }
+
+    fn release(&self, index: i32) {
+        self.free.lock().unwrap().insert(index, true);
+    }
+}
+
+const THREADS: usize = 8;
+const OPS_PER_THREAD: usize = 1_000;
+
+fn bench_legacy(c: &mut Criterion) {
+    c.bench_function("legacy_mutex_hashmap_burst", |b| {
+        b.iter(|| {
+            let list = LegacyFreeList::new();
+            thread::scope(|scope| {
+                for _ in 0..THREADS {
+                    scope.spawn(|| {
+                        for _ in 0..OPS_PER_THREAD {
+                            let index = list.acquire();
+                            list.release(index);
+                        }
+                    });
+                }
+            });
+        });
+    });
+}
+
+fn bench_reserved_pool(c: &mut Criterion) {
+    c.bench_function("reserved_pool_burst", |b| {
+        b.iter(|| {
+            thread::scope(|scope| {
+                for _ in 0..THREADS {
+                    scope.spawn(|| {
+                        for _ in 0..OPS_PER_THREAD {
+                            if let Some(index) = bench_support::acquire_reserved() {
+                                bench_support::release_reserved(index);
+                            }
+                        }
+                    });
+                }
+            });
+        });
+    });
+}
+
+criterion_group!(benches, bench_legacy, bench_reserved_pool);
+criterion_main!(benches);
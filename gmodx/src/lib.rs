@@ -4,7 +4,7 @@ pub mod macros;
 pub mod lua;
 
 pub mod open_close;
-pub use open_close::{is_closed, is_main_thread, is_open};
+pub use open_close::{Realm, current_realm, is_closed, is_main_thread, is_open};
 
 pub use gmodx_macros::*;
 
@@ -18,6 +18,8 @@ pub use next_tick_queue::NextTickQueue;
 mod next_tick;
 pub use next_tick::{async_next_tick, block_until_next_tick, flush_next_tick, next_tick};
 
+pub mod tick;
+
 #[cfg(feature = "tokio")]
 pub mod tokio_tasks;
 
@@ -0,0 +1,121 @@
+use std::any::{Any, TypeId};
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+
+use rustc_hash::{FxBuildHasher, FxHashMap};
+
+use crate::lua;
+use crate::sync::{XCell, XRc, XRef, XRefMut};
+
+struct AppDataMap(UnsafeCell<FxHashMap<TypeId, XRc<dyn Any>>>);
+
+// SAFETY: a `&lua::State` only ever exists while the main Lua lock is held (see
+// `lua::lock`), so access to this map is effectively single-threaded even in the
+// non-`send` build, same invariant as `MainThreadCell` in `lua::lock`.
+unsafe impl Sync for AppDataMap {}
+
+static APP_DATA: AppDataMap = AppDataMap(UnsafeCell::new(FxHashMap::with_hasher(FxBuildHasher)));
+
+fn map() -> &'static mut FxHashMap<TypeId, XRc<dyn Any>> {
+    // SAFETY: see `AppDataMap`'s safety comment above.
+    unsafe { &mut *APP_DATA.0.get() }
+}
+
+/// A borrowed reference to a value stored via [`lua::State::set_app_data`], returned by
+/// [`lua::State::app_data_ref`]. Derefs to `T`.
+pub struct AppDataRef<T: 'static> {
+    guard: XRef<'static, T>,
+    _owner: XRc<XCell<T>>,
+}
+
+impl<T> Deref for AppDataRef<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+/// A mutably-borrowed reference to a value stored via [`lua::State::set_app_data`],
+/// returned by [`lua::State::app_data_mut`]. Derefs to `T`.
+pub struct AppDataRefMut<T: 'static> {
+    guard: XRefMut<'static, T>,
+    _owner: XRc<XCell<T>>,
+}
+
+impl<T> Deref for AppDataRefMut<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for AppDataRefMut<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl lua::State {
+    /// Attaches `value` to this Lua state, keyed by its type. Replaces any value of the
+    /// same type that was set before. Lets binding authors stash per-instance context
+    /// (config, connection pools, caches) on the state without global statics.
+    pub fn set_app_data<T: Any>(&self, value: T) {
+        map().insert(TypeId::of::<T>(), XRc::new(XCell::new(value)));
+    }
+
+    /// Immutably borrows the app data of type `T`, if any was set.
+    #[must_use]
+    pub fn app_data_ref<T: Any>(&self) -> Option<AppDataRef<T>> {
+        let owner: XRc<XCell<T>> = map().get(&TypeId::of::<T>())?.clone().downcast().ok()?;
+
+        #[cfg(not(feature = "send"))]
+        let guard = owner.borrow();
+        #[cfg(feature = "send")]
+        let guard = owner.lock().unwrap();
+
+        // SAFETY: `guard` borrows out of the heap allocation `owner` points to. `owner`
+        // is kept alive for as long as `guard` (it's declared after `guard`, so it drops
+        // after it), which makes extending the borrow to `'static` here sound.
+        let guard: XRef<'static, T> = unsafe { std::mem::transmute(guard) };
+
+        Some(AppDataRef {
+            guard,
+            _owner: owner,
+        })
+    }
+
+    /// Mutably borrows the app data of type `T`, if any was set.
+    #[must_use]
+    pub fn app_data_mut<T: Any>(&self) -> Option<AppDataRefMut<T>> {
+        let owner: XRc<XCell<T>> = map().get(&TypeId::of::<T>())?.clone().downcast().ok()?;
+
+        #[cfg(not(feature = "send"))]
+        let guard = owner.borrow_mut();
+        #[cfg(feature = "send")]
+        let guard = owner.lock().unwrap();
+
+        // SAFETY: see `app_data_ref` above; the same reasoning applies to the mutable
+        // borrow.
+        let guard: XRefMut<'static, T> = unsafe { std::mem::transmute(guard) };
+
+        Some(AppDataRefMut {
+            guard,
+            _owner: owner,
+        })
+    }
+
+    /// Removes the app data of type `T`, if any was set, and returns it. If an
+    /// [`AppDataRef`]/[`AppDataRefMut`] to it is still alive, the value is still removed from
+    /// the state but `None` is returned.
+    pub fn remove_app_data<T: Any>(&self) -> Option<T> {
+        let owner: XRc<XCell<T>> = map().remove(&TypeId::of::<T>())?.downcast().ok()?;
+        let cell = XRc::into_inner(owner)?;
+
+        #[cfg(not(feature = "send"))]
+        return Some(cell.into_inner());
+        #[cfg(feature = "send")]
+        return cell.into_inner().ok();
+    }
+}
@@ -0,0 +1,83 @@
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use crate::lua::thread::{PendingCompletion, register_completion};
+use crate::lua::{self, FromLuaMulti, Function, ToLuaMulti, ffi};
+
+impl Function {
+    /// Calls the function as a coroutine and returns a future that resolves once it runs to
+    /// completion, however many times (and from wherever) it's resumed along the way.
+    ///
+    /// Unlike [`call`](Self::call), this doesn't block the calling thread if the function
+    /// yields (e.g. because it awaits an async userdata method): the returned future just
+    /// waits to be woken once the coroutine's last resume finishes it, wherever that resume
+    /// happens to come from.
+    pub fn call_async<R: FromLuaMulti + Send + 'static>(
+        &self,
+        state: &lua::State,
+        args: impl ToLuaMulti,
+    ) -> CallAsync<R> {
+        let thread = state.create_thread(self.clone());
+        let ret = thread.resume_common(state, args);
+        CallAsync::new(thread, ret)
+    }
+}
+
+enum Pending {
+    /// The initial resume already finished (or failed) synchronously.
+    Settled(lua::Result<()>),
+    /// The coroutine yielded; waiting on whoever eventually resumes it to completion.
+    Waiting(Arc<PendingCompletion>),
+}
+
+/// The [`Future`] returned by [`Function::call_async`].
+pub struct CallAsync<R> {
+    thread: lua::Thread,
+    pending: Pending,
+    _marker: PhantomData<R>,
+}
+
+impl<R: FromLuaMulti + Send + 'static> CallAsync<R> {
+    fn new(thread: lua::Thread, ret: lua::Result<i32>) -> Self {
+        let pending = match ret {
+            Ok(ffi::LUA_YIELD) => Pending::Waiting(register_completion(thread.1.0)),
+            ret => Pending::Settled(ret.map(|_| ())),
+        };
+        CallAsync {
+            thread,
+            pending,
+            _marker: PhantomData,
+        }
+    }
+
+    fn read_results(&self) -> lua::Result<R> {
+        let thread_state = &self.thread.1;
+        let nresults = ffi::lua_gettop(thread_state.0);
+        R::try_from_stack_multi(thread_state, -nresults, nresults).map(|(v, _)| v)
+    }
+}
+
+impl<R: FromLuaMulti + Send + 'static> Future for CallAsync<R> {
+    type Output = lua::Result<R>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // `Thread`/`Pending` are plain data, so moving `self` around under the pin is fine.
+        let this = self.get_mut();
+
+        let outcome = match &this.pending {
+            Pending::Settled(outcome) => outcome.clone(),
+            Pending::Waiting(completion) => match completion.take_outcome() {
+                Some(outcome) => outcome,
+                None => {
+                    completion.set_waker(cx.waker());
+                    return Poll::Pending;
+                }
+            },
+        };
+
+        Poll::Ready(outcome.and_then(|()| this.read_results()))
+    }
+}
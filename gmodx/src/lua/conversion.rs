@@ -1,11 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::ffi::CString;
 
 use bstr::ByteSlice as _;
 use bstr::{BStr, BString};
 
 use crate::lua::traits::{FromLua, ToLua};
-use crate::lua::{self, FromLuaMulti, LightUserData, Nil, Result, Table, ToLuaMulti, ffi};
+use crate::lua::{
+    self, FromLuaMulti, LightUserData, Nil, Result, Table, ToLuaMulti, Value, ValueKind, ffi,
+};
 
 impl<T: ToLua> ToLua for Option<T> {
     #[inline]
@@ -218,11 +220,9 @@ impl<T: ToLua + Clone> ToLua for &[T] {
 impl<T: FromLua> FromLua for Vec<T> {
     fn try_from_stack(state: &lua::State, index: i32) -> Result<Self> {
         let table = Table::try_from_stack(state, index)?;
-        let len = table.raw_len(state);
-        let mut vec = Vec::with_capacity(len);
-        for i in 1..=len {
-            let value = table.raw_get(state, i)?;
-            vec.push(value);
+        let mut vec = Vec::with_capacity(table.raw_len(state));
+        for value in table.sequence_values(state) {
+            vec.push(value?);
         }
         Ok(vec)
     }
@@ -254,25 +254,153 @@ where
 
 impl<K: FromLua + Eq + std::hash::Hash, V: FromLua> FromLua for HashMap<K, V> {
     fn try_from_stack(l: &lua::State, index: i32) -> Result<Self> {
-        if ffi::lua_type(l.0, index) != ffi::LUA_TTABLE {
-            return Err(l.type_error(index, "table"));
-        }
-        let _sg = l.stack_guard(); // to pop any extra values we push
+        let table = Table::try_from_stack(l, index)?;
         let mut map = HashMap::new();
-        let abs_idx = ffi::lua_absindex(l.0, index);
-        // push nil onto the stack to indicate that we want to start iterating
-        ffi::lua_pushnil(l.0);
-        while ffi::lua_next(l.0, abs_idx) != 0 {
-            let v = V::try_from_stack(l, -1)?;
-            let k = K::try_from_stack(l, -2)?;
-            // pop the value, keep the key for the next iteration
-            ffi::lua_pop(l.0, 1);
+        for pair in table.pairs(l) {
+            let (k, v) = pair?;
             map.insert(k, v);
         }
         Ok(map)
     }
 }
 
+impl<K: ToLua, V: ToLua> ToLua for BTreeMap<K, V> {
+    fn push_to_stack(self, l: &lua::State) {
+        let table = l.create_table_with_capacity(0, self.len() as i32);
+        for (k, v) in self {
+            table.raw_set(l, k, v);
+        }
+        table.push_to_stack(l);
+    }
+}
+
+impl<K, V> ToLua for &BTreeMap<K, V>
+where
+    for<'a> &'a K: ToLua,
+    for<'a> &'a V: ToLua,
+{
+    fn push_to_stack(self, l: &lua::State) {
+        let table = l.create_table_with_capacity(0, self.len() as i32);
+        for (k, v) in self {
+            table.raw_set(l, k, v);
+        }
+        table.push_to_stack(l);
+    }
+}
+
+impl<K: FromLua + Ord, V: FromLua> FromLua for BTreeMap<K, V> {
+    fn try_from_stack(l: &lua::State, index: i32) -> Result<Self> {
+        let table = Table::try_from_stack(l, index)?;
+        let mut map = BTreeMap::new();
+        for pair in table.pairs(l) {
+            let (k, v) = pair?;
+            map.insert(k, v);
+        }
+        Ok(map)
+    }
+}
+
+// Sets encode as a table whose keys are the members with `true` values, the idiomatic Lua
+// "set" representation; `FromLua` also accepts a plain 1-indexed array of members.
+
+/// Tells the set-of-keys form (every value is literal `true`) apart from a plain 1-indexed array
+/// of members. `raw_len(state) > 0` alone can't do this: a `HashSet<u32>` like `{1, 2, 3}`
+/// encodes as `{[1]=true, [2]=true, [3]=true}`, whose sequence part *also* has length 3, so a
+/// length check mistakes the `true` sentinels for array members (and worse, silently corrupts a
+/// `HashSet<bool>`, where a sentinel value and a genuine member are both booleans). Checking
+/// that every value is `true` distinguishes the two regardless of what `T` is.
+fn is_set_of_keys_form(state: &lua::State, table: &Table) -> Result<bool> {
+    for pair in table.pairs::<Value, Value>(state) {
+        let (_, value) = pair?;
+        if value.type_kind() != ValueKind::Bool || !bool::try_from_value(value, state)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+impl<T: ToLua> ToLua for HashSet<T> {
+    fn push_to_stack(self, l: &lua::State) {
+        let table = l.create_table_with_capacity(0, self.len() as i32);
+        for member in self {
+            table.raw_set(l, member, true);
+        }
+        table.push_to_stack(l);
+    }
+}
+
+impl<T> ToLua for &HashSet<T>
+where
+    for<'a> &'a T: ToLua,
+{
+    fn push_to_stack(self, l: &lua::State) {
+        let table = l.create_table_with_capacity(0, self.len() as i32);
+        for member in self {
+            table.raw_set(l, member, true);
+        }
+        table.push_to_stack(l);
+    }
+}
+
+impl<T: FromLua + Eq + std::hash::Hash> FromLua for HashSet<T> {
+    fn try_from_stack(state: &lua::State, index: i32) -> Result<Self> {
+        let table = Table::try_from_stack(state, index)?;
+        let mut set = HashSet::new();
+        if is_set_of_keys_form(state, &table)? {
+            for pair in table.pairs::<T, Value>(state) {
+                let (member, _) = pair?;
+                set.insert(member);
+            }
+        } else {
+            for member in table.sequence_values(state) {
+                set.insert(member?);
+            }
+        }
+        Ok(set)
+    }
+}
+
+impl<T: ToLua> ToLua for BTreeSet<T> {
+    fn push_to_stack(self, l: &lua::State) {
+        let table = l.create_table_with_capacity(0, self.len() as i32);
+        for member in self {
+            table.raw_set(l, member, true);
+        }
+        table.push_to_stack(l);
+    }
+}
+
+impl<T> ToLua for &BTreeSet<T>
+where
+    for<'a> &'a T: ToLua,
+{
+    fn push_to_stack(self, l: &lua::State) {
+        let table = l.create_table_with_capacity(0, self.len() as i32);
+        for member in self {
+            table.raw_set(l, member, true);
+        }
+        table.push_to_stack(l);
+    }
+}
+
+impl<T: FromLua + Ord> FromLua for BTreeSet<T> {
+    fn try_from_stack(state: &lua::State, index: i32) -> Result<Self> {
+        let table = Table::try_from_stack(state, index)?;
+        let mut set = BTreeSet::new();
+        if is_set_of_keys_form(state, &table)? {
+            for pair in table.pairs::<T, Value>(state) {
+                let (member, _) = pair?;
+                set.insert(member);
+            }
+        } else {
+            for member in table.sequence_values(state) {
+                set.insert(member?);
+            }
+        }
+        Ok(set)
+    }
+}
+
 #[inline]
 fn from_lua_f64(state: &lua::State, index: i32) -> Result<f64> {
     match ffi::lua_type(state.0, index) {
@@ -297,6 +425,10 @@ macro_rules! impl_big_from_lua {
         impl FromLua for $t {
             #[inline]
             fn try_from_stack(state: &lua::State, index: i32) -> Result<Self> {
+                #[cfg(feature = "integer_subtype")]
+                if ffi::lua_isinteger(state.0, index) {
+                    return Ok(ffi::lua_tointeger(state.0, index) as $t);
+                }
                 match ffi::lua_type(state.0, index) {
                     ffi::LUA_TNUMBER => Ok(ffi::lua_tonumber(state.0, index) as $t),
                     ffi::LUA_TSTRING => BString::try_from_stack(state, index)?
@@ -313,6 +445,10 @@ macro_rules! impl_big_from_lua {
         impl FromLua for $t {
             #[inline]
             fn try_from_stack(state: &lua::State, index: i32) -> Result<Self> {
+                #[cfg(feature = "integer_subtype")]
+                if ffi::lua_isinteger(state.0, index) {
+                    return Ok(ffi::lua_tointeger(state.0, index) as $t);
+                }
                 match ffi::lua_type(state.0, index) {
                     ffi::LUA_TNUMBER => Ok(ffi::lua_tonumber(state.0, index) as $t),
                     ffi::LUA_TSTRING => {
@@ -347,6 +483,15 @@ macro_rules! impl_big_to_lua {
         impl ToLua for $t {
             #[inline]
             fn push_to_stack(self, state: &lua::State) {
+                #[cfg(feature = "integer_subtype")]
+                {
+                    let in_range = (ffi::lua_Integer::MIN as i128..=ffi::lua_Integer::MAX as i128)
+                        .contains(&(self as i128));
+                    if in_range {
+                        ffi::lua_pushinteger(state.0, self as ffi::lua_Integer);
+                        return;
+                    }
+                }
                 if (-9007199254740991..=9007199254740991).contains(&self) {
                     f64::push_to_stack(self as f64, state) // fits in f64
                 } else {
@@ -359,6 +504,13 @@ macro_rules! impl_big_to_lua {
         impl ToLua for $t {
             #[inline]
             fn push_to_stack(self, state: &lua::State) {
+                #[cfg(feature = "integer_subtype")]
+                {
+                    if (self as u128) <= ffi::lua_Integer::MAX as u128 {
+                        ffi::lua_pushinteger(state.0, self as ffi::lua_Integer);
+                        return;
+                    }
+                }
                 if self <= 9007199254740991 {
                     f64::push_to_stack(self as f64, state) // fits in f64
                 } else {
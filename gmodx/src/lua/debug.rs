@@ -1,6 +1,9 @@
+use std::cell::RefCell;
 use std::os::raw::c_char;
 use std::{ffi::CStr, mem::MaybeUninit};
 
+use rustc_hash::FxHashMap;
+
 use crate::lua::{self, ffi};
 
 #[derive(Debug, Clone)]
@@ -74,3 +77,86 @@ impl From<&ffi::lua_Debug> for DebugInfo {
         unsafe { Self::from_raw(raw) }
     }
 }
+
+/// Which events [`lua::State::set_hook`] should fire the callback for.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HookTriggers {
+    /// Fire when Lua enters a function.
+    pub on_calls: bool,
+    /// Fire when Lua returns from a function.
+    pub on_returns: bool,
+    /// Fire before executing each new line of code.
+    pub on_lines: bool,
+    /// Fire every `n` VM instructions.
+    pub every_nth_instruction: Option<u32>,
+}
+
+impl HookTriggers {
+    fn mask(&self) -> i32 {
+        let mut mask = 0;
+        if self.on_calls {
+            mask |= ffi::LUA_MASKCALL;
+        }
+        if self.on_returns {
+            mask |= ffi::LUA_MASKRET;
+        }
+        if self.on_lines {
+            mask |= ffi::LUA_MASKLINE;
+        }
+        if self.every_nth_instruction.is_some() {
+            mask |= ffi::LUA_MASKCOUNT;
+        }
+        mask
+    }
+}
+
+type HookCallback = Box<dyn FnMut(&lua::State, &DebugInfo)>;
+
+// Keyed by `lua_State` pointer rather than a single global slot so a hook can be set on a
+// coroutine without disturbing one already running on its parent (or vice versa). Not `Send`:
+// hooks only ever fire on whichever thread is actually running the Lua VM that owns them.
+thread_local! {
+    static HOOKS: RefCell<FxHashMap<usize, HookCallback>> = RefCell::new(FxHashMap::default());
+}
+
+impl lua::State {
+    /// Installs `callback` to run on the events selected by `triggers`, replacing any hook
+    /// previously set on this state. The callback is invoked with a freshly-queried
+    /// [`DebugInfo`] for whatever frame triggered it; a panic inside it is caught and raised
+    /// to Lua as a normal error instead of unwinding across the C call boundary.
+    pub fn set_hook(&self, triggers: HookTriggers, callback: impl FnMut(&lua::State, &DebugInfo) + 'static) {
+        HOOKS.with(|hooks| {
+            hooks.borrow_mut().insert(self.0 as usize, Box::new(callback));
+        });
+        let count = triggers.every_nth_instruction.unwrap_or(0) as i32;
+        ffi::lua_sethook(self.0, Some(hook_proc), triggers.mask(), count);
+    }
+
+    /// Removes the hook installed by [`Self::set_hook`], if any.
+    pub fn remove_hook(&self) {
+        HOOKS.with(|hooks| {
+            hooks.borrow_mut().remove(&(self.0 as usize));
+        });
+        ffi::lua_sethook(self.0, None, 0, 0);
+    }
+}
+
+extern "C-unwind" fn hook_proc(l: *mut ffi::lua_State, ar: *mut ffi::lua_Debug) {
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        if ffi::lua_getinfo(l, c"nSl".as_ptr(), ar) == 0 {
+            return;
+        }
+        let info = DebugInfo::from(unsafe { &*ar });
+        let state = lua::State(l);
+        HOOKS.with(|hooks| {
+            if let Some(callback) = hooks.borrow_mut().get_mut(&(l as usize)) {
+                callback(&state, &info);
+            }
+        });
+    }));
+
+    if result.is_err() {
+        ffi::lua_pushstring(l, c"panic in debug hook".as_ptr());
+        ffi::lua_error(l);
+    }
+}
@@ -1,4 +1,6 @@
-use crate::lua::{self, Function, ffi, traits::FromLua};
+use std::{ffi::CStr, mem};
+
+use crate::lua::{self, Function, debug::DebugInfo, ffi, traits::FromLua};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -12,8 +14,14 @@ pub enum Error {
     Syntax(String),
 
     /// A runtime error occurred during Lua execution.
-    /// Optionally contains the error message returned by Lua.
-    Runtime(String),
+    ///
+    /// `traceback` is the Lua call stack captured by [`message_handler`] at the point the error
+    /// was raised (`None` for errors that never went through a protected call with that message
+    /// handler installed).
+    Runtime {
+        message: String,
+        traceback: Option<String>,
+    },
 
     /// A generic error represented by a string message.
     Message(String),
@@ -31,6 +39,9 @@ pub enum Error {
         function: String,
         cause: String,
     },
+
+    /// Attempted to resume a coroutine that is already running or has finished/errored.
+    CoroutineUnresumable,
 }
 
 impl std::fmt::Display for Error {
@@ -38,7 +49,10 @@ impl std::fmt::Display for Error {
         match self {
             Error::MemoryAllocation(s) => write!(f, "{}", s),
             Error::Syntax(s) => write!(f, "{}", s),
-            Error::Runtime(s) => write!(f, "{}", s),
+            Error::Runtime { message, traceback } => match traceback {
+                Some(traceback) => write!(f, "{}\n{}", message, traceback),
+                None => write!(f, "{}", message),
+            },
             Error::Message(msg) => write!(f, "{}", msg),
             Error::Unknown { code, message } => {
                 write!(f, "Unknown Lua error (code {}): {}", code, message)
@@ -51,6 +65,7 @@ impl std::fmt::Display for Error {
                 function,
                 cause,
             } => write!(f, "bad argument #{} to '{}' ({})", arg_num, function, cause),
+            Error::CoroutineUnresumable => write!(f, "cannot resume non-suspended coroutine"),
         }
     }
 }
@@ -79,6 +94,11 @@ impl lua::State {
             "pop_error called with non-error return code"
         );
 
+        if let Some(err) = take_wrapped_error(self) {
+            ffi::lua_pop(self.0, 1); // pop the now-emptied wrapped-error userdata
+            return err;
+        }
+
         let err_string = lua::String::try_from_stack(self, -1)
             .expect("this error MUST be a string")
             .to_string();
@@ -87,7 +107,10 @@ impl lua::State {
         match err_code {
             ffi::LUA_ERRMEM => Error::MemoryAllocation(err_string),
             ffi::LUA_ERRSYNTAX => Error::Syntax(err_string),
-            ffi::LUA_ERRRUN | ffi::LUA_ERRERR => Error::Runtime(err_string),
+            ffi::LUA_ERRRUN | ffi::LUA_ERRERR => {
+                let (message, traceback) = split_traceback(err_string);
+                Error::Runtime { message, traceback }
+            }
             _ => Error::Unknown {
                 code: err_code,
                 message: err_string,
@@ -95,8 +118,124 @@ impl lua::State {
         }
     }
 
+    /// Runs a protected call with [`message_handler`] installed as its `errfunc`, so a `Runtime`
+    /// error carries a traceback instead of just the bare error string. The function to call and
+    /// its `nargs` arguments above it must already be on top of the stack; on success they are
+    /// replaced by `nresults` (or however many `LUA_MULTRET` produced) return values, exactly as
+    /// a direct `lua_pcall` would leave them.
     pub(crate) fn protect_lua_call(&self, nargs: i32, nresults: i32) -> Result<()> {
-        let ret = ffi::lua_pcall(self.0, nargs, nresults, 0);
+        let msgh_index = ffi::lua_gettop(self.0) - nargs;
+        ffi::lua_pushcfunction(self.0, Some(message_handler));
+        ffi::lua_insert(self.0, msgh_index);
+
+        let ret = ffi::lua_pcall(self.0, nargs, nresults, msgh_index);
+        ffi::lua_remove(self.0, msgh_index); // message_handler is left on the stack by lua_pcall
+
+        if ret == ffi::LUA_OK {
+            Ok(())
+        } else {
+            Err(self.pop_error(ret))
+        }
+    }
+
+    /// Runs `f` through a `lua_pcall`-protected trampoline instead of calling it directly, so a
+    /// Lua error raised inside `f` (most importantly an out-of-memory error from an allocating
+    /// FFI call) is turned into an `Err` instead of `longjmp`-ing straight past `f`'s Rust stack
+    /// frame (and its destructors) as undefined behavior.
+    ///
+    /// `nargs` values must already be on top of the stack before calling this; `f` receives the
+    /// state positioned exactly as if it had been called directly. Unlike [`Self::protect_lua_call`],
+    /// `f`'s return value (not a count of stack results) becomes this function's `Ok` value, so
+    /// if `f` needs to read a value it left on the stack (e.g. after `lua_gettable`), it should
+    /// do so itself before returning — the whole protected call frame, `f`'s side effects
+    /// included, is unwound once it returns.
+    pub(crate) fn protect_lua_closure<F, R>(&self, nargs: i32, f: F) -> Result<R>
+    where
+        F: FnMut(&lua::State) -> R,
+    {
+        struct Params<F, R> {
+            func: F,
+            result: Option<R>,
+        }
+
+        unsafe extern "C-unwind" fn trampoline<F, R>(l: *mut ffi::lua_State) -> i32
+        where
+            F: FnMut(&lua::State) -> R,
+        {
+            // SAFETY: `protect_lua_closure` below pushes exactly this pointer as the last
+            // (topmost) argument right before calling us through `lua_pcall`, and it stays alive
+            // for the duration of that call.
+            let params = unsafe { &mut *(ffi::lua_touserdata(l, -1) as *mut Params<F, R>) };
+            // Drop the params lightuserdata so `f` sees the stack exactly as `nargs` left it.
+            ffi::lua_pop(l, 1);
+            let state = lua::State(l);
+            params.result = Some((params.func)(&state));
+            0
+        }
+
+        let stack_start = ffi::lua_gettop(self.0) - nargs;
+
+        ffi::lua_pushcfunction(self.0, Some(trampoline::<F, R>));
+        ffi::lua_insert(self.0, stack_start + 1);
+
+        // `nargs` values already sit right above the trampoline after the insert above, so
+        // pushing the params pointer now lands it as the last (topmost) argument.
+        let mut params = Params {
+            func: f,
+            result: None,
+        };
+        ffi::lua_pushlightuserdata(self.0, (&mut params as *mut Params<F, R>).cast());
+
+        // The trampoline always reports 0 Lua results (its real result travels out via
+        // `params.result`), so this call always collapses the whole protected frame away.
+        let ret = ffi::lua_pcall(self.0, nargs + 1, 0, 0);
+        if ret == ffi::LUA_OK {
+            Ok(params.result.take().expect("trampoline did not run"))
+        } else {
+            Err(self.pop_error(ret))
+        }
+    }
+
+    /// Backs the [`protect_lua!`] macro: like [`Self::protect_lua_closure`], but `f` operates
+    /// directly on the raw stack instead of returning a Rust value, and is trusted to leave
+    /// exactly `nresults` Lua values on top of it — the same contract `nresults` has for a plain
+    /// Lua function call. Useful for protected ops (`__newindex`, `__index`, arithmetic and
+    /// comparison metamethods, ...) whose result *is* what they leave on the stack, so there's
+    /// nothing to round-trip through an out-of-band channel.
+    pub(crate) fn protect_lua_stack<F>(&self, nargs: i32, nresults: i32, f: F) -> Result<()>
+    where
+        F: FnMut(*mut ffi::lua_State),
+    {
+        struct Params<F> {
+            func: F,
+            nresults: i32,
+        }
+
+        unsafe extern "C-unwind" fn trampoline<F>(l: *mut ffi::lua_State) -> i32
+        where
+            F: FnMut(*mut ffi::lua_State),
+        {
+            // SAFETY: `protect_lua_stack` below pushes exactly this pointer as the last (topmost)
+            // argument right before calling us through `lua_pcall`, and it stays alive for the
+            // duration of that call.
+            let params = unsafe { &mut *(ffi::lua_touserdata(l, -1) as *mut Params<F>) };
+            // Drop the params lightuserdata so `f` sees the stack exactly as `nargs` left it.
+            ffi::lua_pop(l, 1);
+            (params.func)(l);
+            params.nresults
+        }
+
+        let stack_start = ffi::lua_gettop(self.0) - nargs;
+
+        ffi::lua_pushcfunction(self.0, Some(trampoline::<F>));
+        ffi::lua_insert(self.0, stack_start + 1);
+
+        // `nargs` values already sit right above the trampoline after the insert above, so
+        // pushing the params pointer now lands it as the last (topmost) argument.
+        let mut params = Params { func: f, nresults };
+        ffi::lua_pushlightuserdata(self.0, (&mut params as *mut Params<F>).cast());
+
+        let ret = ffi::lua_pcall(self.0, nargs + 1, nresults, 0);
         if ret == ffi::LUA_OK {
             Ok(())
         } else {
@@ -111,3 +250,156 @@ impl lua::State {
         }
     }
 }
+
+const WRAPPED_ERROR_METATABLE_NAME: &CStr = gmodx_macros::unique_id!(cstr);
+
+/// Boxes `err` into a userdata with a private metatable (`__gc` drops it if it's still there,
+/// `__tostring` formats it via `Display`) and raises it with `lua_error`, instead of flattening
+/// it into a bare string first. A `lua_pcall` that catches this can recover `err` by value via
+/// [`take_wrapped_error`]/[`lua::State::pop_error`] — traceback, variant, everything — instead of
+/// just the stringified message a plain `lua_error(message)` would leave behind.
+pub(crate) fn raise_wrapped_error(state: &lua::State, err: Error) -> ! {
+    let ptr = ffi::lua_newuserdata(state.0, mem::size_of::<Option<Error>>()) as *mut Option<Error>;
+    // SAFETY: the userdata was just allocated with exactly this layout by `lua_newuserdata` above.
+    unsafe { ptr.write(Some(err)) };
+
+    if ffi::luaL_newmetatable(state.0, WRAPPED_ERROR_METATABLE_NAME.as_ptr()) {
+        extern "C-unwind" fn gc_wrapped_error(l: *mut ffi::lua_State) -> i32 {
+            let ptr = ffi::lua_touserdata(l, 1) as *mut Option<Error>;
+            if !ptr.is_null() {
+                // Drops whatever is left: `Some` if nobody ever recovered it (e.g. it propagated
+                // all the way to the top), `None` (a no-op) if `take_wrapped_error` already did.
+                unsafe { std::ptr::drop_in_place(ptr) };
+            }
+            0
+        }
+
+        extern "C-unwind" fn tostring_wrapped_error(l: *mut ffi::lua_State) -> i32 {
+            let ptr = ffi::lua_touserdata(l, 1) as *mut Option<Error>;
+            let message = unsafe { &*ptr }
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_else(|| "(wrapped Rust error already recovered)".to_string());
+            ffi::lua_pushlstring(l, message.as_ptr() as *const i8, message.len());
+            1
+        }
+
+        ffi::lua_pushcclosure(state.0, Some(gc_wrapped_error), 0);
+        ffi::lua_setfield(state.0, -2, c"__gc".as_ptr());
+        ffi::lua_pushcclosure(state.0, Some(tostring_wrapped_error), 0);
+        ffi::lua_setfield(state.0, -2, c"__tostring".as_ptr());
+    }
+    ffi::lua_setmetatable(state.0, -2);
+
+    ffi::lua_error(state.0);
+}
+
+/// Checks whether the value at stack index `idx` of `state` is a [`raise_wrapped_error`]
+/// userdata, without disturbing anything at `idx` itself (only scratch values above it are
+/// pushed and popped). Shared by [`take_wrapped_error`] (which then takes ownership of the
+/// `Error`) and [`message_handler`] (which just needs to know whether to leave the value alone).
+fn is_wrapped_error(state: &lua::State, idx: i32) -> bool {
+    if ffi::lua_type(state.0, idx) != ffi::LUA_TUSERDATA {
+        return false;
+    }
+    if ffi::lua_getmetatable(state.0, idx) == 0 {
+        return false;
+    }
+    // stack: ..., value_metatable
+    ffi::lua_pushstring(state.0, WRAPPED_ERROR_METATABLE_NAME.as_ptr());
+    ffi::lua_rawget(state.0, ffi::LUA_REGISTRYINDEX);
+    // stack: ..., value_metatable, registered_metatable (nil if never registered)
+    let matches = ffi::lua_rawequal(state.0, -1, -2);
+    ffi::lua_pop(state.0, 2); // pop both metatables
+    matches
+}
+
+/// If the value at the top of `state`'s stack is a [`raise_wrapped_error`] userdata, takes its
+/// original `Error` back out, leaving the (now emptied) userdata itself in place — the caller
+/// still needs to pop it. Returns `None` (leaving the stack untouched) for anything else, e.g. a
+/// plain string error, so callers fall back to the usual string-based handling.
+fn take_wrapped_error(state: &lua::State) -> Option<Error> {
+    if !is_wrapped_error(state, -1) {
+        return None;
+    }
+
+    let ptr = ffi::lua_touserdata(state.0, -1) as *mut Option<Error>;
+    // SAFETY: `is_wrapped_error` confirms this userdata was created by `raise_wrapped_error`,
+    // which always sizes and initializes it as `Option<Error>`.
+    unsafe { &mut *ptr }.take()
+}
+
+/// Separates `message_handler`'s combined "message + traceback" string back into its two parts.
+/// `message_handler` always joins them with this exact marker, so a message that happens to
+/// contain it verbatim is the only (practically nonexistent) false-positive case.
+fn split_traceback(full: String) -> (String, Option<String>) {
+    const MARKER: &str = "\nstack traceback:\n";
+    match full.split_once(MARKER) {
+        Some((message, rest)) => (message.to_string(), Some(format!("stack traceback:\n{rest}"))),
+        None => (full, None),
+    }
+}
+
+/// `errfunc` installed by [`lua::State::protect_lua_call`]: while the stack is still unwinding
+/// (before `lua_pcall` starts popping frames), walks the call stack with `lua_getstack`/
+/// `lua_getinfo` and appends a multi-line traceback to the error message, mirroring what
+/// `lua.c`'s standalone interpreter does with `luaL_traceback`. Must leave exactly one value on
+/// the stack, per `lua_pcall`'s errfunc contract.
+extern "C-unwind" fn message_handler(l: *mut ffi::lua_State) -> i32 {
+    let state = lua::State(l);
+    // A `raise_wrapped_error` userdata carries the real `lua::Error` for `pop_error` to recover
+    // by value; stringifying it here (like any other non-string error object) would destroy that
+    // before it ever reaches `pop_error`/`take_wrapped_error`. Leave it on the stack untouched.
+    if is_wrapped_error(&state, 1) {
+        return 1;
+    }
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let state = lua::State(l);
+
+        let message = if ffi::lua_type(l, 1) == ffi::LUA_TSTRING {
+            lua::String::try_from_stack(&state, 1)
+                .map(|s| s.to_string())
+                .unwrap_or_default()
+        } else {
+            format!("(error object is a {} value)", state.type_name(1))
+        };
+
+        // Level 0 is this handler itself; start at level 1 (where the error was raised) and walk
+        // outward until lua_getstack runs out of frames.
+        let mut frames = Vec::new();
+        for level in 1.. {
+            let Some(info) = state.debug_getinfo_at(level, c"Sln") else {
+                break;
+            };
+            frames.push(format_frame(&info));
+        }
+
+        format!("{message}\nstack traceback:\n{}", frames.join("\n"))
+    }));
+
+    let full = match result {
+        Ok(full) => full,
+        Err(_) => "panic while building a Lua traceback".to_string(),
+    };
+    ffi::lua_pushlstring(l, full.as_ptr() as *const i8, full.len());
+    1
+}
+
+fn format_frame(info: &DebugInfo) -> String {
+    let namewhat = info.namewhat.as_ref().map(|s| s.to_string());
+    let what = info.what.as_ref().map(|s| s.to_string());
+
+    let description = match (&namewhat, &info.name) {
+        (Some(namewhat), Some(name)) if !namewhat.is_empty() => {
+            format!("{namewhat} '{name}'")
+        }
+        _ => match what.as_deref() {
+            Some("main") => "main chunk".to_string(),
+            Some("C") => "?".to_string(),
+            _ => format!("function <{}:{}>", info.short_src, info.linedefined),
+        },
+    };
+
+    format!("\t{}:{}: in {}", info.short_src, info.currentline, description)
+}
@@ -192,6 +192,25 @@ pub fn lua_tonumber(l: *mut lua_State, index: i32) -> lua_Number {
     unsafe { FFI.lua_tonumber(l, index) }
 }
 
+// Only available on Lua flavors with a native integer subtype (Lua 5.3+, Luau).
+#[cfg(feature = "integer_subtype")]
+#[inline(always)]
+pub fn lua_isinteger(l: *mut lua_State, index: i32) -> bool {
+    unsafe { FFI.lua_isinteger(l, index) != 0 }
+}
+
+#[cfg(feature = "integer_subtype")]
+#[inline(always)]
+pub fn lua_tointeger(l: *mut lua_State, index: i32) -> lua_Integer {
+    unsafe { FFI.lua_tointeger(l, index) }
+}
+
+#[cfg(feature = "integer_subtype")]
+#[inline(always)]
+pub fn lua_pushinteger(l: *mut lua_State, n: lua_Integer) {
+    unsafe { FFI.lua_pushinteger(l, n) };
+}
+
 #[inline(always)]
 pub fn lua_pushlightuserdata(l: *mut lua_State, p: *mut std::ffi::c_void) {
     unsafe { FFI.lua_pushlightuserdata(l, p) };
@@ -271,6 +290,11 @@ pub fn lua_rawequal(L: *mut lua_State, index1: i32, index2: i32) -> bool {
     unsafe { FFI.lua_rawequal(L, index1, index2) == 1 }
 }
 
+#[inline(always)]
+pub fn lua_equal(L: *mut lua_State, index1: i32, index2: i32) -> bool {
+    unsafe { FFI.lua_equal(L, index1, index2) == 1 }
+}
+
 #[inline(always)]
 pub fn lua_getmetatable(L: *mut lua_State, index: i32) -> i32 {
     unsafe { FFI.lua_getmetatable(L, index) }
@@ -291,6 +315,11 @@ pub fn luaL_ref(L: *mut lua_State, t: i32) -> i32 {
     unsafe { FFI.luaL_ref(L, t) }
 }
 
+#[inline(always)]
+pub fn luaL_unref(L: *mut lua_State, t: i32, r: i32) {
+    unsafe { FFI.luaL_unref(L, t, r) };
+}
+
 #[inline(always)]
 pub fn lua_isnumber(L: *mut lua_State, i: i32) -> i32 {
     unsafe { FFI.lua_isnumber(L, i) }
@@ -374,6 +403,11 @@ pub fn lua_getinfo(L: *mut lua_State, what: *const i8, ar: *mut lua_Debug) -> i3
     unsafe { FFI.lua_getinfo(L, what, ar) }
 }
 
+#[inline(always)]
+pub fn lua_sethook(L: *mut lua_State, f: lua_Hook, mask: i32, count: i32) -> i32 {
+    unsafe { FFI.lua_sethook(L, f, mask, count) }
+}
+
 #[inline(always)]
 pub fn luaL_loadbuffer(
     L: *mut lua_State,
@@ -384,6 +418,11 @@ pub fn luaL_loadbuffer(
     unsafe { FFI.luaL_loadbuffer(L, buff, sz, name) }
 }
 
+#[inline(always)]
+pub fn lua_dump(L: *mut lua_State, writer: lua_Writer, data: *mut ::std::os::raw::c_void) -> i32 {
+    unsafe { FFI.lua_dump(L, writer, data) }
+}
+
 #[inline(always)]
 pub fn lua_yield(L: *mut lua_State, nresults: i32) -> i32 {
     unsafe { FFI.lua_yield(L, nresults) }
@@ -404,7 +443,22 @@ pub fn lua_tothread(L: *mut lua_State, idx: i32) -> *mut lua_State {
     unsafe { FFI.lua_tothread(L, idx) }
 }
 
+#[inline(always)]
+pub fn lua_pushthread(L: *mut lua_State) -> i32 {
+    unsafe { FFI.lua_pushthread(L) }
+}
+
 #[inline(always)]
 pub fn lua_getfenv(L: *mut lua_State, idx: i32) {
     unsafe { FFI.lua_getfenv(L, idx) };
 }
+
+#[inline(always)]
+pub fn lua_setfenv(L: *mut lua_State, idx: i32) -> i32 {
+    unsafe { FFI.lua_setfenv(L, idx) }
+}
+
+#[inline(always)]
+pub fn lua_iscfunction(L: *mut lua_State, idx: i32) -> bool {
+    unsafe { FFI.lua_iscfunction(L, idx) != 0 }
+}
@@ -1,7 +1,7 @@
 use std::{ffi::CStr, fmt::Display, mem};
 
 use crate::lua::{
-    self, FromLuaMulti, StackGuard, ToLuaMulti, Value, ffi,
+    self, FromLuaMulti, StackGuard, Table, ToLuaMulti, Value, ffi,
     traits::{FromLua, ToLua},
     types::{Callback, MaybeSend},
 };
@@ -21,10 +21,7 @@ impl Function {
         (&self.0).push_to_stack(state); // Push the function onto the stack
         args.push_to_stack_multi(state);
         let nargs = ffi::lua_gettop(state.0) - stack_start - 1;
-        match ffi::lua_pcall(state.0, nargs, ffi::LUA_MULTRET, 0) {
-            ffi::LUA_OK => {}
-            res => return Err(state.pop_error(res)),
-        }
+        state.protect_lua_call(nargs, ffi::LUA_MULTRET)?;
         let nresults = ffi::lua_gettop(state.0) - stack_start;
         R::try_from_stack_multi(state, stack_start + 1, nresults).map(|(v, _)| v)
     }
@@ -50,10 +47,7 @@ impl Function {
         (&self.0).push_to_stack(state); // Push the function onto the stack
         args.push_to_stack_multi(state);
         let nargs = ffi::lua_gettop(state.0) - stack_start - 1;
-        match ffi::lua_pcall(state.0, nargs, 0, 0) {
-            ffi::LUA_OK => Ok(()),
-            res => Err(state.pop_error(res)),
-        }
+        state.protect_lua_call(nargs, 0)
     }
 
     /// Same as [`call_no_rets`], but logs any errors that occur.
@@ -68,6 +62,34 @@ impl Function {
         }
         res
     }
+
+    /// Returns the function's environment table (its `_ENV`/fenv, in LuaJIT's 5.1 semantics),
+    /// or `None` if it's a C function, which has no fenv.
+    pub fn environment(&self, state: &lua::State) -> Option<Table> {
+        let _sg = state.stack_guard();
+        #[allow(clippy::needless_borrow)]
+        (&self.0).push_to_stack(state);
+        if ffi::lua_iscfunction(state.0, -1) {
+            return None;
+        }
+        ffi::lua_getfenv(state.0, -1);
+        Table::try_from_stack(state, -1).ok()
+    }
+
+    /// Replaces the function's environment table. Returns an error if it's a C function.
+    pub fn set_environment(&self, state: &lua::State, env: Table) -> lua::Result<()> {
+        let _sg = state.stack_guard();
+        #[allow(clippy::needless_borrow)]
+        (&self.0).push_to_stack(state);
+        if ffi::lua_iscfunction(state.0, -1) {
+            return Err(lua::Error::Message(
+                "cannot set the environment of a C function".into(),
+            ));
+        }
+        env.push_to_stack(state);
+        ffi::lua_setfenv(state.0, -2);
+        Ok(())
+    }
 }
 
 const CLOSURE_GC_METATABLE_NAME: &CStr = gmodx_macros::unique_id!(cstr);
@@ -81,6 +103,27 @@ impl lua::State {
         self.create_function_impl(callback)
     }
 
+    /// Like [`create_function`](Self::create_function), but `f` returns a future instead of
+    /// running to completion synchronously. The future is spawned onto the tokio task
+    /// runtime; calling the resulting function from a coroutine suspends it via `lua_yield`
+    /// until the future resolves, then resumes it with the converted return values (or raises
+    /// the error string) - the same mechanism as an async userdata method (see
+    /// [`Methods::add_async_method`](crate::lua::Methods::add_async_method)).
+    #[cfg(feature = "tokio")]
+    pub fn create_async_function<A, Fut, R>(
+        &self,
+        f: impl Fn(&lua::State, A) -> Fut + MaybeSend + 'static,
+    ) -> Function
+    where
+        A: FromLuaMulti,
+        Fut: std::future::Future<Output = lua::Result<R>> + Send + 'static,
+        R: ToLuaMulti + Send + 'static,
+    {
+        let callback = crate::lua::userdata::async_method::trampoline(f);
+        let raw = self.create_function_impl(callback);
+        crate::lua::userdata::async_method::wrap_async_callback(self, raw)
+    }
+
     pub(crate) fn create_function_impl(&self, func: Callback) -> Function {
         let callback_ptr =
             ffi::lua_newuserdata(self.0, mem::size_of::<Callback>()) as *mut Callback;
@@ -116,6 +159,75 @@ impl lua::State {
 
         Function(Value::pop_from_stack(self))
     }
+
+    /// Compiles `source` — Lua text, or a binary chunk previously produced by
+    /// [`Self::dump_function`] — into a [`Function`], named `chunk_name` for error messages and
+    /// `debug.getinfo`'s `source` field. `luaL_loadbuffer` auto-detects a binary chunk by its
+    /// leading signature byte, so this also backs [`Self::load_bytecode`].
+    pub fn load_buffer(
+        &self,
+        source: &[u8],
+        chunk_name: impl AsRef<CStr>,
+    ) -> lua::Result<Function> {
+        let ret = ffi::luaL_loadbuffer(
+            self.0,
+            source.as_ptr() as *const i8,
+            source.len(),
+            chunk_name.as_ref().as_ptr(),
+        );
+        if ret == ffi::LUA_OK {
+            Ok(Function(Value::pop_from_stack(self)))
+        } else {
+            Err(self.pop_error(ret))
+        }
+    }
+
+    /// Loads a binary chunk dumped by [`Self::dump_function`], so addons can ship or memoize
+    /// compiled Lua and skip the parser on reload. Just [`Self::load_buffer`] under a more
+    /// specific name: `luaL_loadbuffer` already handles binary chunks (LuaJIT prefixes them with
+    /// a signature byte it detects automatically), so a malformed or version-incompatible one
+    /// surfaces the same way a syntax error would, as `Error::Syntax`.
+    pub fn load_bytecode(
+        &self,
+        bytes: &[u8],
+        chunk_name: impl AsRef<CStr>,
+    ) -> lua::Result<Function> {
+        self.load_buffer(bytes, chunk_name)
+    }
+
+    /// Serializes the function at the top of the stack (which must already be loaded, e.g. via
+    /// [`Self::load_buffer`], but not yet called) into a byte buffer via `lua_dump`, so it can be
+    /// cached and fed back through [`Self::load_bytecode`] later to skip reparsing it. Doesn't
+    /// touch the stack beyond reading the function off its top.
+    ///
+    /// `strip` is accepted for call-site symmetry with later Lua versions' `lua_dump`, but this
+    /// crate targets LuaJIT's Lua 5.1 API, whose `lua_dump` has no strip flag to forward it to —
+    /// it's currently ignored.
+    pub fn dump_function(&self, strip: bool) -> lua::Result<Vec<u8>> {
+        let _ = strip;
+
+        extern "C-unwind" fn writer(
+            _l: *mut ffi::lua_State,
+            p: *const std::ffi::c_void,
+            size: usize,
+            data: *mut std::ffi::c_void,
+        ) -> i32 {
+            let buf = unsafe { &mut *(data as *mut Vec<u8>) };
+            let bytes = unsafe { std::slice::from_raw_parts(p as *const u8, size) };
+            buf.extend_from_slice(bytes);
+            0
+        }
+
+        let mut buf = Vec::new();
+        let ret = ffi::lua_dump(self.0, Some(writer), (&mut buf as *mut Vec<u8>).cast());
+        if ret == 0 {
+            Ok(buf)
+        } else {
+            Err(lua::Error::Message(
+                "lua_dump failed to serialize function".into(),
+            ))
+        }
+    }
 }
 
 extern "C-unwind" fn rust_closure_callback(state: *mut ffi::lua_State) -> i32 {
@@ -126,11 +238,18 @@ extern "C-unwind" fn rust_closure_callback(state: *mut ffi::lua_State) -> i32 {
             let func = unsafe { &*data_ptr };
             match func(&l) {
                 Ok(v) => return v,
-                Err(err) => {
-                    let err_str = err.to_string();
-                    ffi::lua_pushlstring(l.0, err_str.as_ptr() as *const i8, err_str.len());
-                    drop(err_str); // make sure to drop before lua_error
-                }
+                // If the callback failed with one of our own `lua::Error`s, wrap it in a userdata
+                // instead of stringifying it here, so a `lua_pcall` catching it (e.g.
+                // `protect_lua_call`) can recover the original error by value instead of just its
+                // message. Anything else (a foreign error type) falls back to the old behavior.
+                Err(err) => match err.downcast::<lua::Error>() {
+                    Ok(err) => lua::raise_wrapped_error(&l, *err),
+                    Err(err) => {
+                        let err_str = err.to_string();
+                        ffi::lua_pushlstring(l.0, err_str.as_ptr() as *const i8, err_str.len());
+                        drop(err_str); // make sure to drop before lua_error
+                    }
+                },
             }
         } else {
             ffi::lua_pushstring(l.0, c"attempt to call a nil value".as_ptr());
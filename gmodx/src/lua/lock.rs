@@ -1,4 +1,4 @@
-use std::cell::UnsafeCell;
+use std::{cell::UnsafeCell, time::Duration};
 
 use xutex::{ReentrantMutex, ReentrantMutexGuard};
 
@@ -80,6 +80,51 @@ where
     Some(f(&guard))
 }
 
+/// Returns immediately instead of blocking: `None` if `LUA_LOCK` is currently contended, same as
+/// if the state were closed.
+pub fn try_lock() -> Option<StateGuard> {
+    get_state_guard(LUA_LOCK.try_lock()?)
+}
+
+pub fn with_try_lock<F, R>(f: F) -> Option<R>
+where
+    F: FnOnce(&lua::State) -> R,
+{
+    let guard = try_lock()?;
+    Some(f(&guard))
+}
+
+/// Like [`lock`], but gives up after `timeout` instead of blocking indefinitely.
+pub fn lock_timeout(timeout: Duration) -> Option<StateGuard> {
+    get_state_guard(LUA_LOCK.try_lock_for(timeout)?)
+}
+
+pub fn with_lock_timeout<F, R>(timeout: Duration, f: F) -> Option<R>
+where
+    F: FnOnce(&lua::State) -> R,
+{
+    let guard = lock_timeout(timeout)?;
+    Some(f(&guard))
+}
+
+/// Like [`lock_async`], but gives up after `timeout` instead of waiting indefinitely. Dropping
+/// `tokio::time::timeout`'s future on expiry drops the inner `lock_async()` future with it, which
+/// deregisters this call as a waiter on `LUA_LOCK` — so a timed-out caller doesn't leave a
+/// phantom waiter behind for `yield_lock`'s periodic bump to keep servicing forever.
+#[cfg(feature = "tokio")]
+pub async fn lock_async_timeout(timeout: Duration) -> Option<StateGuard> {
+    tokio::time::timeout(timeout, lock_async()).await.ok()?
+}
+
+#[cfg(feature = "tokio")]
+pub async fn with_lock_async_timeout<F, R>(timeout: Duration, f: F) -> Option<R>
+where
+    F: FnOnce(&lua::State) -> R,
+{
+    let guard = lock_async_timeout(timeout).await?;
+    Some(f(&guard))
+}
+
 inventory::submit! {
     crate::open_close::new(
         0,
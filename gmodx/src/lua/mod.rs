@@ -3,13 +3,20 @@
 pub(crate) mod ffi;
 
 pub(crate) mod lock;
-pub use lock::{lock, lock_async, with_lock, with_lock_async};
+pub use lock::{
+    lock, lock_async, lock_timeout, try_lock, with_lock, with_lock_async, with_lock_timeout,
+    with_try_lock,
+};
+#[cfg(feature = "tokio")]
+pub use lock::{lock_async_timeout, with_lock_async_timeout};
 
 mod state;
 pub use state::State;
 
 mod conversion;
 mod value_ref;
+#[cfg(feature = "internal-benchmarks")]
+pub use value_ref::bench_support;
 
 mod types;
 pub use types::{LightUserData, Nil, Number, String};
@@ -18,10 +25,11 @@ mod value;
 pub use value::{MultiValue, Value, ValueKind};
 
 mod multi_value_of;
-pub use multi_value_of::MultiValueOf;
+pub use multi_value_of::{MultiValueOf, Variadic};
 
 mod error;
 pub use error::{Error, Result};
+pub(crate) use error::raise_wrapped_error;
 
 mod stack_guard;
 pub use stack_guard::StackGuard;
@@ -32,17 +40,32 @@ pub use table::{Table, table};
 mod thread;
 pub use thread::{Thread, ThreadStatus};
 
+mod registry;
+pub use registry::RegistryKey;
+
+mod app_data;
+pub use app_data::{AppDataRef, AppDataRefMut};
+
+#[cfg(feature = "serde")]
+mod serde;
+#[cfg(feature = "serde")]
+pub use serde::{Deserializer, EnumEncoding, LuaSerdeExt, SerializeOptions, Serializer};
+
 mod traits;
 pub use traits::{FromLua, FromLuaMulti, ObjectLike, ToLua, ToLuaMulti};
 
 mod function;
 pub use function::{Function, IntoLuaFunction};
 
+mod call_async;
+
 mod userdata;
 pub use userdata::{
-    AnyUserData, MethodsBuilder as Methods, ScopedUserData, ScopedUserDataRef, UserData,
-    UserDataRef,
+    AnyUserData, FieldsBuilder, MetaMethod, MethodsBuilder as Methods, ScopedUserData,
+    ScopedUserDataRef, UserData, UserDataRef,
 };
+#[cfg(feature = "send")]
+pub use userdata::{ScopedUserDataRw, ScopedUserDataRwRef};
 
 mod debug;
 
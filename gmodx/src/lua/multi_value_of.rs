@@ -5,6 +5,11 @@ use crate::lua::{self, FromLua, FromLuaMulti, ToLua, ToLuaMulti};
 #[derive(Default, Debug, Clone)]
 pub struct MultiValueOf<T>(pub Vec<T>);
 
+/// Alias for [`MultiValueOf`] matching mlua's naming: as the trailing parameter of an
+/// [`IntoLuaFunction`](crate::lua::IntoLuaFunction) callback, it soaks up every remaining
+/// argument (`Variadic<T>`), and as a return value it spreads a `Vec` into multiple results.
+pub type Variadic<T> = MultiValueOf<T>;
+
 impl<T> Deref for MultiValueOf<T> {
     type Target = Vec<T>;
     fn deref(&self) -> &Self::Target {
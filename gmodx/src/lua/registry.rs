@@ -0,0 +1,84 @@
+use std::sync::Mutex;
+
+use crate::lua::{self, FromLua, Result, ToLua, ffi};
+use crate::next_tick::next_tick;
+
+/// Registry slots freed by a [`RegistryKey`] drop that haven't been unreffed yet, because
+/// no Lua lock was held at the time to run the next-tick queue (or the queue simply hasn't
+/// flushed yet). Drained by [`lua::State::expire_registry_values`].
+static PENDING_UNREFS: Mutex<Vec<i32>> = Mutex::new(Vec::new());
+
+/// A saved reference to a Lua value stored in `LUA_REGISTRYINDEX`, created via
+/// [`lua::State::create_registry_value`]. Dropping it frees the registry slot on the
+/// main thread (via the next-tick queue), so it never needs a `&State` to be reclaimed.
+#[derive(Debug)]
+pub struct RegistryKey(i32);
+
+impl RegistryKey {
+    #[inline]
+    pub(crate) fn id(&self) -> i32 {
+        self.0
+    }
+}
+
+impl Drop for RegistryKey {
+    fn drop(&mut self) {
+        // LUA_REFNIL/LUA_NOREF are reserved sentinels, not real slots to unref.
+        if self.0 == ffi::LUA_REFNIL || self.0 == ffi::LUA_NOREF {
+            return;
+        }
+        // Queued rather than unreffed here directly: `drop` can run on any thread (e.g. a
+        // worker thread holding the last clone of a spawned future's captured key), and only
+        // the main thread may touch the Lua state. The id is held in `PENDING_UNREFS` until
+        // `expire_registry_values` actually runs, which this merely requests via next_tick.
+        PENDING_UNREFS.lock().unwrap().push(self.0);
+        next_tick(|l| l.expire_registry_values());
+    }
+}
+
+impl lua::State {
+    /// Stores `value` in the Lua registry and returns a key that can later be used to
+    /// retrieve it via [`Self::registry_value`] or release it via
+    /// [`Self::remove_registry_value`].
+    ///
+    /// Mirrors mlua's fix for the recycled-slot bug: `nil` is never stored at an
+    /// arbitrary ref slot (doing so corrupts the registry's length-based free-slot
+    /// search), it's mapped to the dedicated `LUA_REFNIL` slot instead.
+    pub fn create_registry_value(&self, value: impl ToLua) -> RegistryKey {
+        value.push_to_stack(self);
+        if ffi::lua_type(self.0, -1) == ffi::LUA_TNIL {
+            ffi::lua_pop(self.0, 1);
+            return RegistryKey(ffi::LUA_REFNIL);
+        }
+        RegistryKey(ffi::luaL_ref(self.0, ffi::LUA_REGISTRYINDEX))
+    }
+
+    /// Retrieves the value previously stored by [`Self::create_registry_value`].
+    pub fn registry_value<T: FromLua>(&self, key: &RegistryKey) -> Result<T> {
+        ffi::lua_rawgeti(self.0, ffi::LUA_REGISTRYINDEX, key.id());
+        let result = T::try_from_stack(self, -1);
+        ffi::lua_pop(self.0, 1);
+        result
+    }
+
+    /// Releases a value stored via [`Self::create_registry_value`] immediately, instead
+    /// of waiting for the key to be dropped.
+    pub fn remove_registry_value(&self, key: RegistryKey) {
+        if key.id() != ffi::LUA_REFNIL && key.id() != ffi::LUA_NOREF {
+            ffi::luaL_unref(self.0, ffi::LUA_REGISTRYINDEX, key.id());
+        }
+        std::mem::forget(key); // already freed above; don't free it again on drop
+    }
+
+    /// Unrefs every registry slot freed by a [`RegistryKey`] drop since the last sweep.
+    ///
+    /// Queued automatically via the next-tick queue whenever a key is dropped, but harmless
+    /// (and sometimes necessary, e.g. to flush keys dropped during a stretch where no Lua
+    /// lock was held to run that queue) to call directly too.
+    pub fn expire_registry_values(&self) {
+        let pending = std::mem::take(&mut *PENDING_UNREFS.lock().unwrap());
+        for id in pending {
+            ffi::luaL_unref(self.0, ffi::LUA_REGISTRYINDEX, id);
+        }
+    }
+}
@@ -0,0 +1,795 @@
+//! Bridges [`serde::Serialize`]/[`serde::Deserialize`] to Lua [`Value`]s via [`LuaSerdeExt`].
+//! Sequences map to 1-indexed array tables, maps/structs to key-value tables, `Option::None`
+//! to `nil`, and externally-tagged enums to single-key tables (`{ [variant] = payload }`),
+//! matching mlua's `LuaSerdeExt`'s defaults. Both are configurable via [`SerializeOptions`]
+//! ([`LuaSerdeExt::to_value_with_options`]): [`EnumEncoding`] picks how enum variants are
+//! encoded, and `omit_nil_fields` picks whether a `None`/unit field is written as an explicit
+//! `nil` or left out of the table entirely.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use serde::de::{self, IntoDeserializer};
+use serde::ser;
+
+use crate::lua::table::PairsIter;
+use crate::lua::{self, Error, FromLua, Result, Table, ToLua, Value, ValueKind};
+
+/// Tables currently being walked along the *current* deserialization path, keyed by
+/// [`Table::identity`]. Shared (via `Rc`) by every [`Deserializer`]/`*Access` spawned from the
+/// same top-level call, so a table that references one of its own ancestors is caught as a cycle
+/// — but a table reused in two unrelated branches (a DAG, not a cycle) is not, since entries are
+/// removed once their subtree finishes.
+type Visited = Rc<RefCell<HashSet<usize>>>;
+
+/// How [`Serializer`] should encode an enum variant.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EnumEncoding {
+    /// A unit variant becomes a plain string (`"Foo"`); a variant carrying a payload becomes a
+    /// single-key table (`{ Foo = payload }`). This is the default, and matches mlua's
+    /// `LuaSerdeExt` behavior.
+    #[default]
+    ExternallyTagged,
+    /// Every variant, unit included, becomes a single-key table (`{ Foo = true }` for a unit
+    /// variant). Useful when the Lua side always expects a table shape regardless of which
+    /// variant it got, rather than having to branch on `type(v) == "string"`.
+    Table,
+}
+
+/// Knobs for [`Serializer`], set via [`lua::State::to_value_with_options`]. `Default::default()`
+/// matches mlua's `LuaSerdeExt` behavior: [`EnumEncoding::ExternallyTagged`], `None`/unit fields
+/// written as an explicit `nil` rather than omitted.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SerializeOptions {
+    pub enum_encoding: EnumEncoding,
+    /// When `true`, a struct/map field whose value serializes to `nil` (an `Option::None` or a
+    /// unit value) is left out of the table entirely instead of being written as an explicit
+    /// `nil` key. Doesn't affect sequence elements, which always need their positional index.
+    pub omit_nil_fields: bool,
+}
+
+impl ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Message(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Message(msg.to_string())
+    }
+}
+
+/// A [`serde::Serializer`] that turns a `T: Serialize` into a Lua [`Value`]. Use
+/// [`lua::State::to_value`]/[`lua::State::to_value_with_options`] instead of constructing this
+/// directly.
+pub struct Serializer<'a> {
+    state: &'a lua::State,
+    options: SerializeOptions,
+}
+
+macro_rules! serialize_via_to_value {
+    ($($method:ident: $t:ty),* $(,)?) => {$(
+        fn $method(self, v: $t) -> Result<Value> {
+            Ok(v.to_value(self.state))
+        }
+    )*};
+}
+
+impl<'a> ser::Serializer for Serializer<'a> {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SerializeVec<'a>;
+    type SerializeTuple = SerializeVec<'a>;
+    type SerializeTupleStruct = SerializeVec<'a>;
+    type SerializeTupleVariant = SerializeTupleVariant<'a>;
+    type SerializeMap = SerializeMap<'a>;
+    type SerializeStruct = SerializeMap<'a>;
+    type SerializeStructVariant = SerializeStructVariant<'a>;
+
+    serialize_via_to_value! {
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_i128: i128,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u128: u128,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_str: &str,
+    }
+
+    /// Unlike the other integer widths, `u64` gets its own check instead of going through
+    /// [`serialize_via_to_value!`]: its plain `ToLua` impl silently falls back to a string for
+    /// values that don't fit a Lua number, which would round-trip fine on its own but is the
+    /// wrong behavior for a serde bridge meant to preserve a numeric type across the boundary, so
+    /// this rejects it instead.
+    fn serialize_u64(self, v: u64) -> Result<Value> {
+        const MAX_SAFE_INTEGER: u64 = 9007199254740991; // 2^53 - 1
+        if v > MAX_SAFE_INTEGER {
+            return Err(Error::Message(format!(
+                "integer {v} is too large to represent as a Lua number (max {MAX_SAFE_INTEGER})"
+            )));
+        }
+        Ok(v.to_value(self.state))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value> {
+        Ok(bstr::BStr::new(v).to_value(self.state))
+    }
+
+    fn serialize_none(self) -> Result<Value> {
+        Ok(lua::Nil.to_value(self.state))
+    }
+
+    fn serialize_some<T: ser::Serialize + ?Sized>(self, value: &T) -> Result<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value> {
+        Ok(lua::Nil.to_value(self.state))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value> {
+        match self.options.enum_encoding {
+            EnumEncoding::ExternallyTagged => Ok(variant.to_value(self.state)),
+            EnumEncoding::Table => {
+                let state = self.state;
+                let table = state.create_table_with_capacity(0, 1);
+                table.raw_set(state, variant, true);
+                Ok(table.to_value(state))
+            }
+        }
+    }
+
+    fn serialize_newtype_struct<T: ser::Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ser::Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        let state = self.state;
+        let options = self.options;
+        let payload = value.serialize(Serializer { state, options })?;
+        let table = state.create_table_with_capacity(0, 1);
+        table.raw_set(state, variant, payload);
+        Ok(table.to_value(state))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeVec<'a>> {
+        Ok(SerializeVec {
+            state: self.state,
+            options: self.options,
+            table: self.state.create_table_with_capacity(len.unwrap_or(0) as i32, 0),
+            index: 1,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeVec<'a>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeVec<'a>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeTupleVariant<'a>> {
+        Ok(SerializeTupleVariant {
+            state: self.state,
+            options: self.options,
+            variant,
+            table: self.state.create_table_with_capacity(len as i32, 0),
+            index: 1,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap<'a>> {
+        Ok(SerializeMap {
+            state: self.state,
+            options: self.options,
+            table: self.state.create_table_with_capacity(0, len.unwrap_or(0) as i32),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap<'a>> {
+        Ok(SerializeMap {
+            state: self.state,
+            options: self.options,
+            table: self.state.create_table_with_capacity(0, len as i32),
+            key: None,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeStructVariant<'a>> {
+        Ok(SerializeStructVariant {
+            state: self.state,
+            options: self.options,
+            variant,
+            table: self.state.create_table_with_capacity(0, len as i32),
+        })
+    }
+}
+
+pub struct SerializeVec<'a> {
+    state: &'a lua::State,
+    options: SerializeOptions,
+    table: Table,
+    index: i32,
+}
+
+impl<'a> SerializeVec<'a> {
+    fn push_element<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let v = value.serialize(Serializer {
+            state: self.state,
+            options: self.options,
+        })?;
+        self.table.raw_set(self.state, self.index, v);
+        self.index += 1;
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeSeq for SerializeVec<'a> {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(self.table.to_value(self.state))
+    }
+}
+
+impl<'a> ser::SerializeTuple for SerializeVec<'a> {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(self.table.to_value(self.state))
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for SerializeVec<'a> {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(self.table.to_value(self.state))
+    }
+}
+
+pub struct SerializeTupleVariant<'a> {
+    state: &'a lua::State,
+    options: SerializeOptions,
+    variant: &'static str,
+    table: Table,
+    index: i32,
+}
+
+impl<'a> ser::SerializeTupleVariant for SerializeTupleVariant<'a> {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let v = value.serialize(Serializer {
+            state: self.state,
+            options: self.options,
+        })?;
+        self.table.raw_set(self.state, self.index, v);
+        self.index += 1;
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value> {
+        let outer = self.state.create_table_with_capacity(0, 1);
+        outer.raw_set(self.state, self.variant, self.table);
+        Ok(outer.to_value(self.state))
+    }
+}
+
+pub struct SerializeMap<'a> {
+    state: &'a lua::State,
+    options: SerializeOptions,
+    table: Table,
+    key: Option<Value>,
+}
+
+impl<'a> ser::SerializeMap for SerializeMap<'a> {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: ser::Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(Serializer {
+            state: self.state,
+            options: self.options,
+        })?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        let v = value.serialize(Serializer {
+            state: self.state,
+            options: self.options,
+        })?;
+        if self.options.omit_nil_fields && v.type_kind() == ValueKind::Nil {
+            return Ok(());
+        }
+        self.table.raw_set(self.state, key, v);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(self.table.to_value(self.state))
+    }
+}
+
+impl<'a> ser::SerializeStruct for SerializeMap<'a> {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        let v = value.serialize(Serializer {
+            state: self.state,
+            options: self.options,
+        })?;
+        if self.options.omit_nil_fields && v.type_kind() == ValueKind::Nil {
+            return Ok(());
+        }
+        self.table.raw_set(self.state, key, v);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(self.table.to_value(self.state))
+    }
+}
+
+pub struct SerializeStructVariant<'a> {
+    state: &'a lua::State,
+    options: SerializeOptions,
+    variant: &'static str,
+    table: Table,
+}
+
+impl<'a> ser::SerializeStructVariant for SerializeStructVariant<'a> {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        let v = value.serialize(Serializer {
+            state: self.state,
+            options: self.options,
+        })?;
+        if self.options.omit_nil_fields && v.type_kind() == ValueKind::Nil {
+            return Ok(());
+        }
+        self.table.raw_set(self.state, key, v);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value> {
+        let outer = self.state.create_table_with_capacity(0, 1);
+        outer.raw_set(self.state, self.variant, self.table);
+        Ok(outer.to_value(self.state))
+    }
+}
+
+/// A [`serde::Deserializer`] that reconstructs a `T: Deserialize` from a Lua [`Value`].
+/// Use [`lua::State::from_value`] instead of constructing this directly.
+pub struct Deserializer<'a> {
+    state: &'a lua::State,
+    value: Value,
+    visited: Visited,
+}
+
+impl<'a> Deserializer<'a> {
+    fn root(state: &'a lua::State, value: Value) -> Self {
+        Deserializer {
+            state,
+            value,
+            visited: Rc::new(RefCell::new(HashSet::new())),
+        }
+    }
+
+    fn child(&self, value: Value) -> Self {
+        Deserializer {
+            state: self.state,
+            value,
+            visited: Rc::clone(&self.visited),
+        }
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for Deserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let kind = self.value.type_kind();
+        let Deserializer {
+            state,
+            value,
+            visited,
+        } = self;
+        match kind {
+            ValueKind::Nil => visitor.visit_unit(),
+            ValueKind::Bool => visitor.visit_bool(bool::try_from_value(value, state)?),
+            ValueKind::Number => visitor.visit_f64(f64::try_from_value(value, state)?),
+            ValueKind::String => {
+                let s = bstr::BString::try_from_value(value, state)?;
+                match s.to_str() {
+                    Ok(s) => visitor.visit_str(s),
+                    Err(_) => visitor.visit_bytes(&s),
+                }
+            }
+            ValueKind::Table => {
+                let table = Table::try_from_value(value, state)?;
+                let id = table.identity();
+                if !visited.borrow_mut().insert(id) {
+                    return Err(Error::Message(
+                        "cannot deserialize a self-referential Lua table".to_string(),
+                    ));
+                }
+
+                let result = if table.raw_len(state) > 0 {
+                    visitor.visit_seq(SeqAccess {
+                        state,
+                        table,
+                        index: 0,
+                        visited: Rc::clone(&visited),
+                    })
+                } else {
+                    visitor.visit_map(MapAccess::new(state, table, Rc::clone(&visited)))
+                };
+
+                visited.borrow_mut().remove(&id);
+                result
+            }
+            other => Err(Error::Type {
+                expected: "a serializable value".to_string(),
+                got: other.as_str().to_string(),
+            }),
+        }
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.value.type_kind() {
+            ValueKind::Nil => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        let Deserializer {
+            state,
+            value,
+            visited,
+        } = self;
+        match value.type_kind() {
+            ValueKind::String => {
+                let s = bstr::BString::try_from_value(value, state)?;
+                let s = s
+                    .to_str()
+                    .map_err(|_| Error::Message("enum variant name must be utf-8".to_string()))?
+                    .to_string();
+                visitor.visit_enum(s.into_deserializer())
+            }
+            ValueKind::Table => {
+                let table = Table::try_from_value(value, state)?;
+                let (variant, payload) = table
+                    .pairs::<lua::String, Value>(state)
+                    .next()
+                    .ok_or_else(|| {
+                        Error::Message("expected a single-key table for an enum".to_string())
+                    })?;
+                let variant = variant
+                    .to_str()
+                    .map_err(|_| Error::Message("enum variant name must be utf-8".to_string()))?
+                    .to_string();
+                visitor.visit_enum(EnumAccess {
+                    state,
+                    variant,
+                    value: payload,
+                    visited,
+                })
+            }
+            other => Err(Error::Type {
+                expected: "an enum".to_string(),
+                got: other.as_str().to_string(),
+            }),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct SeqAccess<'a> {
+    state: &'a lua::State,
+    table: Table,
+    index: usize,
+    visited: Visited,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for SeqAccess<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>> {
+        self.index += 1;
+        if self.index > self.table.raw_len(self.state) {
+            return Ok(None);
+        }
+        let value: Value = self.table.raw_get(self.state, self.index)?;
+        seed.deserialize(Deserializer {
+            state: self.state,
+            value,
+            visited: Rc::clone(&self.visited),
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.table.raw_len(self.state))
+    }
+}
+
+struct MapAccess<'a> {
+    state: &'a lua::State,
+    iter: PairsIter<Value, Value>,
+    next_value: Option<Value>,
+    visited: Visited,
+}
+
+impl<'a> MapAccess<'a> {
+    fn new(state: &'a lua::State, table: Table, visited: Visited) -> Self {
+        MapAccess {
+            state,
+            iter: table.pairs(state),
+            next_value: None,
+            visited,
+        }
+    }
+}
+
+impl<'de, 'a> de::MapAccess<'de> for MapAccess<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        match self.iter.next() {
+            Some(Ok((key, value))) => {
+                self.next_value = Some(value);
+                seed.deserialize(Deserializer {
+                    state: self.state,
+                    value: key,
+                    visited: Rc::clone(&self.visited),
+                })
+                .map(Some)
+            }
+            Some(Err(err)) => Err(err),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let value = self
+            .next_value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(Deserializer {
+            state: self.state,
+            value,
+            visited: Rc::clone(&self.visited),
+        })
+    }
+}
+
+struct EnumAccess<'a> {
+    state: &'a lua::State,
+    variant: String,
+    value: Value,
+    visited: Visited,
+}
+
+impl<'de, 'a> de::EnumAccess<'de> for EnumAccess<'a> {
+    type Error = Error;
+    type Variant = VariantAccess<'a>;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant)> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((
+            variant,
+            VariantAccess {
+                state: self.state,
+                value: self.value,
+                visited: self.visited,
+            },
+        ))
+    }
+}
+
+struct VariantAccess<'a> {
+    state: &'a lua::State,
+    value: Value,
+    visited: Visited,
+}
+
+impl<'de, 'a> de::VariantAccess<'de> for VariantAccess<'a> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(Deserializer {
+            state: self.state,
+            value: self.value,
+            visited: self.visited,
+        })
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_seq(
+            Deserializer {
+                state: self.state,
+                value: self.value,
+                visited: self.visited,
+            },
+            visitor,
+        )
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_map(
+            Deserializer {
+                state: self.state,
+                value: self.value,
+                visited: self.visited,
+            },
+            visitor,
+        )
+    }
+}
+
+/// Brings [`serde::Serialize`]/[`serde::Deserialize`] conversions to [`lua::State`],
+/// mirroring mlua's `LuaSerdeExt`. Must be in scope to call
+/// [`to_value`](Self::to_value)/[`from_value`](Self::from_value).
+pub trait LuaSerdeExt {
+    /// Converts `value` into a Lua [`Value`] via its [`serde::Serialize`] impl, using
+    /// [`SerializeOptions::default`].
+    fn to_value<T: ser::Serialize + ?Sized>(&self, value: &T) -> Result<Value>;
+
+    /// Like [`Self::to_value`], but with explicit [`SerializeOptions`] instead of the defaults.
+    fn to_value_with_options<T: ser::Serialize + ?Sized>(
+        &self,
+        value: &T,
+        options: SerializeOptions,
+    ) -> Result<Value>;
+
+    /// Reconstructs a `T` from a Lua [`Value`] via its [`serde::Deserialize`] impl.
+    fn from_value<T: de::DeserializeOwned>(&self, value: Value) -> Result<T>;
+}
+
+impl LuaSerdeExt for lua::State {
+    fn to_value<T: ser::Serialize + ?Sized>(&self, value: &T) -> Result<Value> {
+        self.to_value_with_options(value, SerializeOptions::default())
+    }
+
+    fn to_value_with_options<T: ser::Serialize + ?Sized>(
+        &self,
+        value: &T,
+        options: SerializeOptions,
+    ) -> Result<Value> {
+        value.serialize(Serializer { state: self, options })
+    }
+
+    fn from_value<T: de::DeserializeOwned>(&self, value: Value) -> Result<T> {
+        T::deserialize(Deserializer::root(self, value))
+    }
+}
+
+impl Table {
+    /// Reconstructs a `T` from this table via its [`serde::Deserialize`] impl. Shorthand for
+    /// `state.from_value::<T>(self.to_value(state))`.
+    pub fn deserialize<T: de::DeserializeOwned>(&self, state: &lua::State) -> Result<T> {
+        state.from_value(self.clone().to_value(state))
+    }
+}
+
+impl lua::State {
+    /// Pushes `value` onto the stack via its [`serde::Serialize`] impl. Shorthand for
+    /// `self.to_value(value)?.push_to_stack(self)`.
+    pub fn to_lua_value<T: ser::Serialize + ?Sized>(&self, value: &T) -> Result<()> {
+        self.to_value(value)?.push_to_stack(self);
+        Ok(())
+    }
+
+    /// Reconstructs a `T` from the stack at `index` via its [`serde::Deserialize`] impl.
+    /// Shorthand for `self.from_value(Value::from_stack(self, index))`.
+    pub fn from_lua_value<T: de::DeserializeOwned>(&self, index: i32) -> Result<T> {
+        self.from_value(Value::from_stack(self, index))
+    }
+}
@@ -35,28 +35,56 @@ impl Table {
         self.get_protected(state, key)
     }
 
-    // TODO: should make it call __len, lua 5.1 does not invoke __len, has to be implemented manually
+    /// Like Lua's `#` operator, except it also honors a `__len` metamethod: the 5.1 VM GMod ships
+    /// doesn't invoke `__len` for `#` (unlike 5.2+), so this checks for one manually and falls
+    /// back to [`Self::raw_len`] when the table has no metatable, or its metatable has no
+    /// `__len` function. See [`Self::raw_len`] for the metamethod-free variant.
     pub fn len(&self, state: &lua::State) -> Result<usize> {
-        Ok(self.raw_len(state))
-    }
-
-    pub fn raw_set(&self, state: &lua::State, key: impl ToLua, value: impl ToLua) {
         let _sg = state.stack_guard();
 
-        self.push_to_stack(state); // push the table
-        push_atleast_one(state, key); // push the key
-        push_atleast_one(state, value); // push the value
-        ffi::lua_rawset(state.0, -3);
+        self.push_to_stack(state); // stack: table
+        if ffi::lua_getmetatable(state.0, -1) == 0 {
+            return Ok(self.raw_len(state));
+        }
+        // stack: table, metatable
+
+        ffi::lua_pushstring(state.0, c"__len".as_ptr());
+        ffi::lua_rawget(state.0, -2); // stack: table, metatable, metatable.__len
+        if ffi::lua_type(state.0, -1) != ffi::LUA_TFUNCTION {
+            return Ok(self.raw_len(state));
+        }
+
+        ffi::lua_remove(state.0, -2); // stack: table, __len
+        ffi::lua_insert(state.0, -2); // stack: __len, table
+        state.protect_lua_call(1, 1)?;
+        // stack: the single result of __len(table)
+
+        let len = i64::try_from_stack(state, -1)?;
+        usize::try_from(len)
+            .map_err(|_| lua::Error::Message("__len returned a negative length".to_string()))
     }
 
-    pub fn raw_get<V: FromLua>(&self, state: &lua::State, key: impl ToLua) -> Result<V> {
-        let _sg = state.stack_guard();
+    // `state` is only used to assert we're on the main thread; the table already lives on the
+    // ref thread (`self.0.ref_state()`), so the actual get/set runs entirely there, without
+    // disturbing (or even touching) `state`'s own stack. See the comment in `ValueRef::push` for
+    // why pushing onto the ref thread itself is safe.
+    pub fn raw_set(&self, _: &lua::State, key: impl ToLua, value: impl ToLua) {
+        let ref_state = self.0.ref_state();
+        let _sg = ref_state.stack_guard();
+
+        push_atleast_one(&ref_state, key); // push the key
+        push_atleast_one(&ref_state, value); // push the value
+        ffi::lua_rawset(ref_state.0, self.0.index());
+    }
 
-        self.push_to_stack(state); // push the table
-        push_atleast_one(state, key); // push the key
-        ffi::lua_rawget(state.0, -2);
+    pub fn raw_get<V: FromLua>(&self, _: &lua::State, key: impl ToLua) -> Result<V> {
+        let ref_state = self.0.ref_state();
+        let _sg = ref_state.stack_guard();
 
-        V::try_from_stack(state, -1)
+        push_atleast_one(&ref_state, key); // push the key
+        ffi::lua_rawget(ref_state.0, self.0.index());
+
+        V::try_from_stack(&ref_state, -1)
     }
 
     // the lua state is only used to ensure we are on main thread
@@ -64,6 +92,84 @@ impl Table {
         ffi::lua_rawlen(self.0.ref_state().0, self.0.index())
     }
 
+    /// Appends `value` at `raw_len() + 1`. Matches `table.insert(t, value)`, but via
+    /// [`Self::raw_set`] so it never triggers a metamethod.
+    pub fn raw_push(&self, state: &lua::State, value: impl ToLua) {
+        let len = self.raw_len(state);
+        self.raw_set(state, len + 1, value);
+    }
+
+    /// Removes and returns the element at `raw_len()`, shrinking the table by one. Matches
+    /// `table.remove(t)`, but via raw accessors.
+    pub fn raw_pop<V: FromLua>(&self, state: &lua::State) -> Result<V> {
+        let len = self.raw_len(state);
+        if len == 0 {
+            return Err(lua::Error::Message("raw_pop: table is empty".to_string()));
+        }
+        let value = self.raw_get(state, len)?;
+        self.raw_set(state, len, Nil);
+        Ok(value)
+    }
+
+    /// Shifts elements `pos..=raw_len()` up by one and sets `value` at `pos`. Matches
+    /// `table.insert(t, pos, value)`, but via raw accessors so it never triggers a metamethod.
+    /// Inserting past the end (`pos > raw_len() + 1`) just sets at `pos`, same as any other
+    /// out-of-border [`Self::raw_set`] would.
+    pub fn raw_insert(&self, state: &lua::State, pos: usize, value: impl ToLua) -> Result<()> {
+        if pos == 0 {
+            return Err(lua::Error::Message(
+                "raw_insert: position must be >= 1".to_string(),
+            ));
+        }
+
+        let mut i = self.raw_len(state);
+        while i >= pos {
+            let shifted: Value = self.raw_get(state, i)?;
+            self.raw_set(state, i + 1, shifted);
+            i -= 1;
+        }
+        self.raw_set(state, pos, value);
+        Ok(())
+    }
+
+    /// Removes the element at `pos`, shifting `pos+1..=raw_len()` down by one, and returns it.
+    /// Matches `table.remove(t, pos)`, but via raw accessors. Errors if `pos` falls outside
+    /// `1..=raw_len()`.
+    pub fn raw_remove<V: FromLua>(&self, state: &lua::State, pos: usize) -> Result<V> {
+        let len = self.raw_len(state);
+        if pos == 0 || pos > len {
+            return Err(lua::Error::Message(format!(
+                "raw_remove: position {pos} out of bounds (table has {len} elements)"
+            )));
+        }
+
+        let removed = self.raw_get(state, pos)?;
+        for i in pos..len {
+            let shifted: Value = self.raw_get(state, i + 1)?;
+            self.raw_set(state, i, shifted);
+        }
+        self.raw_set(state, len, Nil);
+        Ok(removed)
+    }
+
+    /// Checks a table's array part element-by-element against a Rust slice, using raw
+    /// (non-metamethod) equality per element. Matches mlua's `PartialEq<[T]>` for tables.
+    pub fn eq_slice<T: ToLua + Clone>(&self, state: &lua::State, slice: &[T]) -> bool {
+        if self.raw_len(state) != slice.len() {
+            return false;
+        }
+        for (i, item) in slice.iter().enumerate() {
+            let value: Value = match self.raw_get(state, i + 1) {
+                Ok(value) => value,
+                Err(_) => return false,
+            };
+            if !value.raw_equals(state, &item.clone().to_value(state)) {
+                return false;
+            }
+        }
+        true
+    }
+
     // the lua state is only used to ensure we are on main thread
     pub fn has_metatable(&self, _: &lua::State) -> bool {
         let thread = self.0.ref_state();
@@ -79,7 +185,6 @@ impl Table {
     pub fn ipairs<V: FromLua>(&self, state: &lua::State) -> IPairsIter<V> {
         IPairsIter {
             table: self.clone(),
-            state: state.clone(),
             index: 0,
             len: self.raw_len(state),
             _phantom: std::marker::PhantomData,
@@ -97,6 +202,42 @@ impl Table {
         }
     }
 
+    /// Like [`Self::ipairs`], but yields only the values (no index) and propagates conversion
+    /// errors instead of silently ending the iteration at the first one, pulling one element per `raw_get` step instead
+    /// of materializing the whole sequence up front.
+    #[inline]
+    pub fn sequence_values<T: FromLua>(&self, state: &lua::State) -> SequenceIter<T> {
+        SequenceIter {
+            table: self.clone(),
+            state: state.clone(),
+            index: 0,
+            done: false,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns `true` when the table has no sequence element at index `1` and no key at all,
+    /// checked with a single `raw_get` and a single `lua_next` step rather than computing `len`.
+    pub fn is_empty(&self, state: &lua::State) -> bool {
+        let _sg = state.stack_guard();
+
+        self.push_to_stack(state); // push the table
+        ffi::lua_rawgeti(state.0, -1, 1);
+        let has_index_one = ffi::lua_type(state.0, -1) != ffi::LUA_TNIL;
+        ffi::lua_pop(state.0, 1); // pop the value from rawgeti
+        if has_index_one {
+            return false;
+        }
+
+        ffi::lua_pushnil(state.0); // key to start iterating from
+        let has_any_key = ffi::lua_next(state.0, -2) != 0;
+        if has_any_key {
+            ffi::lua_pop(state.0, 2); // pop the key and value
+        }
+
+        !has_any_key
+    }
+
     pub fn set_metatable(&self, _: &lua::State, metatable: Option<Table>) {
         let ref_thread = self.0.ref_state().0;
         if let Some(metatable) = &metatable {
@@ -115,19 +256,13 @@ impl Table {
     ) -> Result<()> {
         let _sg = state.stack_guard();
 
-        unsafe extern "C-unwind" fn safe_settable(state: *mut ffi::lua_State) -> i32 {
-            // stack: table, key, value
-            ffi::lua_settable(state, -3);
-            0
-        }
-
-        ffi::lua_pushcfunction(state.0, Some(safe_settable));
         self.push_to_stack(state); // push the table
         push_atleast_one(state, key); // push the key
         push_atleast_one(state, value); // push the value
-        state.protect_lua_call(3, 0)?;
-
-        Ok(())
+        protect_lua!(state, 3, 0, |l| {
+            // stack: table, key, value
+            ffi::lua_settable(l, -3);
+        })
     }
 
     pub(crate) fn get_protected<V: FromLua>(
@@ -137,17 +272,13 @@ impl Table {
     ) -> Result<V> {
         let _sg = state.stack_guard();
 
-        unsafe extern "C-unwind" fn safe_gettable(state: *mut ffi::lua_State) -> i32 {
-            // stack: table, key
-            ffi::lua_gettable(state, -2);
-            1
-        }
-
-        ffi::lua_pushcfunction(state.0, Some(safe_gettable));
         self.push_to_stack(state); // push the table
         push_atleast_one(state, key); // push the key
-        state.protect_lua_call(2, 1)?;
-
+        protect_lua!(state, 2, 1, |l| {
+            // stack: table, key
+            ffi::lua_gettable(l, -2);
+        })?;
+        // stack: the single result of gettable
         V::try_from_stack(state, -1)
     }
 }
@@ -161,6 +292,19 @@ impl lua::State {
         lua::ffi::lua_createtable(self.0, narr, nrec);
         Table(Value::pop_from_stack(self))
     }
+
+    /// Like [`Self::create_table_with_capacity`], but runs `lua_createtable` through a
+    /// protected call, so a failed allocation surfaces as an `Err` instead of a `longjmp`
+    /// unwinding straight through these Rust frames. Mirrors the technique `Table` already
+    /// uses for `set_protected`/`get_protected`.
+    pub fn try_create_table_with_capacity(&self, narr: i32, nrec: i32) -> Result<Table> {
+        let _sg = self.stack_guard();
+
+        self.protect_lua_closure(0, |state| {
+            ffi::lua_createtable(state.0, narr, nrec);
+            Table(Value::pop_from_stack(state))
+        })
+    }
 }
 
 impl ToLua for Table {
@@ -184,6 +328,25 @@ impl ToLua for &Table {
     }
 }
 
+/// Tables compare by raw pointer identity (the same Lua table value), not by `__eq` or
+/// structural content. Use [`Value::equals`] for metamethod-aware comparison, or
+/// [`Table::eq_slice`] to compare a table's array part against a Rust slice.
+impl PartialEq for Table {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl Table {
+    /// A stable per-table identity (its address in the ref thread), for identity-based tracking
+    /// (e.g. the serde bridge's cycle detection) where `PartialEq`'s bool isn't enough.
+    pub(crate) fn identity(&self) -> usize {
+        ffi::lua_topointer(self.0.ref_state().0, self.0.index()) as usize
+    }
+}
+
+impl Eq for Table {}
+
 impl FromLua for Table {
     fn try_from_stack(state: &lua::State, index: i32) -> Result<Self> {
         match lua::ffi::lua_type(state.0, index) {
@@ -228,7 +391,6 @@ impl ObjectLike for Table {
 
 pub struct IPairsIter<V> {
     table: Table,
-    state: lua::State,
     index: usize,
     len: usize,
     _phantom: std::marker::PhantomData<V>,
@@ -243,12 +405,12 @@ impl<V: FromLua> Iterator for IPairsIter<V> {
         }
         self.index += 1;
 
-        let _sg = self.state.stack_guard();
+        let ref_state = self.table.0.ref_state();
+        let _sg = ref_state.stack_guard();
 
-        (&self.table).push_to_stack(&self.state);
-        ffi::lua_rawgeti(self.state.0, -1, self.index as i32);
+        ffi::lua_rawgeti(ref_state.0, self.table.0.index(), self.index as i32);
 
-        V::try_from_stack(&self.state, -1)
+        V::try_from_stack(&ref_state, -1)
             .ok()
             .map(|value| (self.index, value))
     }
@@ -263,7 +425,7 @@ pub struct PairsIter<K, V> {
 }
 
 impl<K: FromLua, V: FromLua> Iterator for PairsIter<K, V> {
-    type Item = (K, V);
+    type Item = Result<(K, V)>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.done {
@@ -283,10 +445,49 @@ impl<K: FromLua, V: FromLua> Iterator for PairsIter<K, V> {
         self.key = Value::from_stack(&self.state, -2);
 
         // stack: table, key, value
-        let v = V::try_from_stack(&self.state, -1).ok()?;
-        let k = K::try_from_stack(&self.state, -2).ok()?;
+        let pair = V::try_from_stack(&self.state, -1)
+            .and_then(|v| K::try_from_stack(&self.state, -2).map(|k| (k, v)));
+
+        if pair.is_err() {
+            self.done = true;
+        }
+
+        Some(pair)
+    }
+}
+
+pub struct SequenceIter<T> {
+    table: Table,
+    state: lua::State,
+    index: i32,
+    done: bool,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: FromLua> Iterator for SequenceIter<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        self.index += 1;
+
+        let _sg = self.state.stack_guard();
+        (&self.table).push_to_stack(&self.state);
+        ffi::lua_rawgeti(self.state.0, -1, self.index);
+
+        if ffi::lua_type(self.state.0, -1) == ffi::LUA_TNIL {
+            self.done = true;
+            return None;
+        }
+
+        let value = T::try_from_stack(&self.state, -1);
+        if value.is_err() {
+            self.done = true;
+        }
 
-        Some((k, v))
+        Some(value)
     }
 }
 
@@ -1,3 +1,7 @@
+use std::ffi::CStr;
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+
 use crate::lua::{self, FromLua, FromLuaMulti, Function, State, ToLua, ToLuaMulti, Value, ffi};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -5,9 +9,67 @@ pub enum ThreadStatus {
     Resumable,
     Yielded,
     Running,
+    /// Ran to completion (successfully) and has nothing left to resume. Distinct from
+    /// `Resumable`: `lua_status` alone reports `LUA_OK` for both a fresh, never-started
+    /// coroutine and one that finished normally, so this crate tracks completion itself (see
+    /// [`mark_finished`]) to tell the two apart.
+    Dead,
     Error,
 }
 
+const FINISHED_MARKER_TABLE_NAME: &CStr = gmodx_macros::unique_id!(cstr);
+
+/// Ensures the finished-coroutine marker table exists in the registry (creating it, with a
+/// `__mode = "k"` metatable, the first time it's needed) and leaves it on top of `l`'s stack.
+///
+/// Keying this table by the coroutine *value* itself, rather than by its raw pointer in some
+/// side `HashSet`, is what lets [`Thread::status`] tell a finished coroutine apart from a fresh
+/// one created later at the same address: once a coroutine marked here becomes otherwise
+/// unreachable, the `__mode = "k"` metatable lets Lua's GC drop its entry on its own, so a
+/// pointer the allocator later reuses for a brand-new coroutine never finds a stale entry.
+fn push_finished_marker_table(l: *mut ffi::lua_State) {
+    if ffi::luaL_newmetatable(l, FINISHED_MARKER_TABLE_NAME.as_ptr()) {
+        ffi::lua_createtable(l, 0, 1);
+        ffi::lua_pushstring(l, c"k".as_ptr());
+        ffi::lua_setfield(l, -2, c"__mode".as_ptr());
+        ffi::lua_setmetatable(l, -2);
+    }
+}
+
+fn mark_finished(state: &lua::State, thread: &Value) {
+    let _sg = state.stack_guard();
+    push_finished_marker_table(state.0);
+    thread.push_to_stack(state);
+    ffi::lua_pushboolean(state.0, 1);
+    ffi::lua_rawset(state.0, -3);
+}
+
+fn is_finished(state: &lua::State, thread: &Value) -> bool {
+    let _sg = state.stack_guard();
+    push_finished_marker_table(state.0);
+    thread.push_to_stack(state);
+    ffi::lua_rawget(state.0, -2);
+    ffi::lua_toboolean(state.0, -1)
+}
+
+fn clear_finished(state: &lua::State, thread: &Value) {
+    let _sg = state.stack_guard();
+    push_finished_marker_table(state.0);
+    thread.push_to_stack(state);
+    ffi::lua_pushnil(state.0);
+    ffi::lua_rawset(state.0, -3);
+}
+
+/// A Lua coroutine. Stored as a [`Value`] (so it lives in the ref thread like any other Lua
+/// value and can cross the Rust/Lua boundary via `FromLua`/`ToLua`), paired with the
+/// `lua::State` wrapping its own separate stack, since that's what `resume`/`status` actually
+/// operate on.
+///
+/// [`Self::resume`] and [`Self::resume_status`] cover the same ground a single
+/// `resume::<R>() -> Result<ThreadStatus<R>>` would: `resume` is for callers that only care
+/// about the yielded/returned values, `resume_status` for callers that also need to know
+/// whether the coroutine is still resumable afterwards. Splitting them avoids making every
+/// caller of the common case destructure a status they don't need.
 pub struct Thread(pub(crate) Value, pub(crate) lua::State);
 
 #[cfg(feature = "send")]
@@ -40,7 +102,34 @@ impl Thread {
         Ok(())
     }
 
-    fn resume_common(&self, l: &State, args: impl ToLuaMulti) -> lua::Result<()> {
+    /// Like [`Self::resume`], but also reports whether the coroutine yielded again
+    /// (`ThreadStatus::Yielded`) or ran to completion (`ThreadStatus::Dead`, i.e. there's
+    /// nothing left to resume), instead of treating both as plain success.
+    pub fn resume_status<R: FromLuaMulti>(
+        &self,
+        l: &State,
+        args: impl ToLuaMulti,
+    ) -> lua::Result<(ThreadStatus, R)> {
+        let ret = self.resume_common(l, args)?;
+        let status = if ret == ffi::LUA_YIELD {
+            ThreadStatus::Yielded
+        } else {
+            ThreadStatus::Dead
+        };
+
+        let thread_state = &self.1;
+        let nresults = ffi::lua_gettop(thread_state.0);
+        let (value, _) = R::try_from_stack_multi(thread_state, -nresults, nresults)?;
+        Ok((status, value))
+    }
+
+    /// Resumes the coroutine, returning the raw `lua_resume` return code
+    /// (`LUA_OK`/`LUA_YIELD`) on success.
+    ///
+    /// `pub(crate)` so [`Function::call_async`](crate::lua::Function::call_async) can drive a
+    /// coroutine's first resume itself while leaving later ones (and the stack results they
+    /// leave behind) to interpret.
+    pub(crate) fn resume_common(&self, l: &State, args: impl ToLuaMulti) -> lua::Result<i32> {
         match self.status(l) {
             ThreadStatus::Resumable | ThreadStatus::Yielded => {}
             _ => return Err(lua::Error::CoroutineUnresumable),
@@ -49,10 +138,24 @@ impl Thread {
         let thread_state = &self.1;
         let nargs = args.push_to_stack_multi_count(thread_state);
         let ret = ffi::lua_resume(thread_state.0, nargs);
-        match ret {
-            ffi::LUA_OK | ffi::LUA_YIELD => Ok(()),
+        let result = match ret {
+            ffi::LUA_OK | ffi::LUA_YIELD => Ok(ret),
             _ => Err(thread_state.pop_error(ret)),
+        };
+
+        if ret == ffi::LUA_OK {
+            // Finished normally: lua_status will report LUA_OK forever after, same as a fresh
+            // coroutine, so remember this ourselves for `status` to tell the two apart.
+            mark_finished(thread_state, &self.0);
         }
+
+        if ret != ffi::LUA_YIELD {
+            // the coroutine is done (successfully or not): anyone awaiting it via
+            // `Function::call_async` can now be woken.
+            complete(thread_state.0, result.as_ref().map(|_| ()).map_err(Clone::clone));
+        }
+
+        result
     }
 
     pub fn status(&self, l: &State) -> ThreadStatus {
@@ -61,9 +164,9 @@ impl Thread {
             return ThreadStatus::Running;
         }
         let status = ffi::lua_status(thread_state.0);
-        // let top = ffi::lua_gettop(thread_state.0);
         match status {
             ffi::LUA_YIELD => ThreadStatus::Yielded,
+            ffi::LUA_OK if is_finished(thread_state, &self.0) => ThreadStatus::Dead,
             ffi::LUA_OK => ThreadStatus::Resumable,
             _ => ThreadStatus::Error,
         }
@@ -72,7 +175,11 @@ impl Thread {
     pub fn reset(&self, l: &State, func: Function) -> lua::Result<()> {
         let status = self.status(l);
         match status {
-            ThreadStatus::Resumable => {
+            ThreadStatus::Resumable | ThreadStatus::Dead => {
+                // Unlike `create_thread`, this reuses the same coroutine object rather than
+                // creating a new one, so the marker table's weak key is still alive and won't
+                // clear itself; clear it explicitly so `status` doesn't keep reporting `Dead`.
+                clear_finished(&self.1, &self.0);
                 ffi::lua_settop(self.1.0, 0);
                 func.push_to_stack(&self.1);
                 Ok(())
@@ -88,6 +195,11 @@ impl Thread {
 impl lua::State {
     pub fn create_thread(&self, func: Function) -> Thread {
         let thread_ptr = ffi::new_thread(self.0);
+        // No need to worry about the allocator having reused this pointer from some
+        // garbage-collected dead coroutine: `new_thread` always creates a genuinely new
+        // coroutine *object*, and the `__mode = "k"` marker table (see
+        // `push_finished_marker_table`) is guaranteed to have dropped the old object's entry
+        // during its collection, before that memory could be handed back out for this one.
         let thread_state = lua::State(thread_ptr);
         func.push_to_stack(&thread_state);
         Thread(Value::pop_from_stack(self), thread_state)
@@ -117,3 +229,55 @@ impl FromLua for Thread {
         }
     }
 }
+
+/// Lets `Function::call_async` learn when a coroutine it's driving finishes, even if the
+/// resume that finishes it is performed elsewhere (e.g. by an async userdata method's
+/// trampoline resuming the same coroutine it yielded from).
+pub(crate) struct PendingCompletion {
+    waker: Mutex<Option<Waker>>,
+    outcome: Mutex<Option<lua::Result<()>>>,
+}
+
+impl PendingCompletion {
+    pub(crate) fn set_waker(&self, waker: &Waker) {
+        *self.waker.lock().unwrap() = Some(waker.clone());
+    }
+
+    pub(crate) fn take_outcome(&self) -> Option<lua::Result<()>> {
+        self.outcome.lock().unwrap().take()
+    }
+}
+
+static PENDING: Mutex<Vec<(usize, Arc<PendingCompletion>)>> = Mutex::new(Vec::new());
+
+/// Registers interest in `thread_ptr`'s completion, returning the (possibly already
+/// existing) slot to poll via [`PendingCompletion::take_outcome`].
+pub(crate) fn register_completion(thread_ptr: *mut ffi::lua_State) -> Arc<PendingCompletion> {
+    let key = thread_ptr as usize;
+    let mut pending = PENDING.lock().unwrap();
+    if let Some((_, completion)) = pending.iter().find(|(k, _)| *k == key) {
+        return completion.clone();
+    }
+    let completion = Arc::new(PendingCompletion {
+        waker: Mutex::new(None),
+        outcome: Mutex::new(None),
+    });
+    pending.push((key, completion.clone()));
+    completion
+}
+
+// Called from `resume_common` once a coroutine stops yielding (finished or errored).
+fn complete(thread_ptr: *mut ffi::lua_State, outcome: lua::Result<()>) {
+    let key = thread_ptr as usize;
+    let mut pending = PENDING.lock().unwrap();
+    let Some(pos) = pending.iter().position(|(k, _)| *k == key) else {
+        return; // nobody is awaiting this coroutine via call_async
+    };
+    let (_, completion) = pending.remove(pos);
+    drop(pending);
+
+    *completion.outcome.lock().unwrap() = Some(outcome);
+    if let Some(waker) = completion.waker.lock().unwrap().take() {
+        waker.wake();
+    }
+}
@@ -1,13 +1,39 @@
+use std::cell::RefCell;
 use std::ffi::c_void;
+use std::sync::Mutex;
 
 use crate::lua::{
-    self, FromLua, FromLuaMulti, Function, ObjectLike, Result, Table, ToLua, ToLuaMulti, Value,
-    ffi, value_ref::ValueRef,
+    self, Error, FromLua, FromLuaMulti, Function, ObjectLike, Result, Table, ToLua, ToLuaMulti,
+    UserData, Value, ffi, value_ref::ValueRef,
 };
 
 #[derive(Clone, Debug)]
 pub struct AnyUserData(pub(crate) ValueRef);
 
+/// Leaked registry index of the shared "destructed" metatable installed by
+/// [`AnyUserData::take`]: its `__index`/`__newindex`/`__call` all raise a Lua error instead of
+/// touching the (now freed, by Rust's side) userdata memory.
+static DESTRUCTED_METATABLE: Mutex<Option<i32>> = Mutex::new(None);
+
+inventory::submit! {
+    crate::open_close::new(
+        0,
+        "userdata_destructed_metatable",
+        |l| {
+            let chunk = l.load_buffer(b"
+                local function raise() error('userdata has been destructed', 2) end
+                return { __index = raise, __newindex = raise, __call = raise }
+            ", c"ud_destructed").expect("failed to load destructed metatable chunk");
+
+            let metatable: Table = chunk.call(l, ()).expect("failed to build destructed metatable");
+            *DESTRUCTED_METATABLE.lock().unwrap() = Some(metatable.0.leak_index());
+        },
+        |_| {
+            *DESTRUCTED_METATABLE.lock().unwrap() = None;
+        },
+    )
+}
+
 // We have to force them to pass lua::State here to ensure they are on the main thread
 // without having to check it each time nor have panics at runtime
 
@@ -38,8 +64,61 @@ impl AnyUserData {
             Err(l.type_error(index, type_name))
         }
     }
+
+    /// Extracts the inner `T` out of a live, directly-owned (not `XRc`-shared) userdata before
+    /// garbage collection, mirroring mlua's destructed-userdata handling. The `TYPES` entry is
+    /// removed first so `__gc` becomes a no-op for this userdata, and the userdata's metatable
+    /// is swapped to a shared one whose `__index`/`__newindex`/`__call` all raise a Lua error,
+    /// so any later access to the now-empty handle fails cleanly instead of reading freed
+    /// memory.
+    pub fn take<T: UserData + 'static>(self, l: &lua::State) -> Result<T> {
+        if !self.is::<RefCell<T>>(l) {
+            return Err(Error::Message(format!(
+                "cannot take '{}': userdata does not directly own a {} (it may be XRc-shared, \
+                 of a different type, or already destructed)",
+                T::name(),
+                T::name()
+            )));
+        }
+
+        let cell_ptr = self.ptr() as *mut RefCell<T>;
+
+        // Make sure nothing still holds a live `Ref`/`RefMut` borrowed from a `UserDataRef<T>`
+        // pointing at this same userdata before we move its contents out from under it.
+        unsafe { &*cell_ptr }
+            .try_borrow_mut()
+            .map_err(|err| Error::Message(format!("cannot take '{}': {}", T::name(), err)))?;
+
+        // SAFETY: `is::<RefCell<T>>` above confirmed the userdata stores exactly a
+        // `RefCell<T>`, and the borrow check above confirmed nothing still references its
+        // contents. Removing the pointer from `TYPES` first ensures `__gc`'s
+        // `drop_userdata_at` becomes a no-op, so these bytes are never dropped a second time.
+        super::TYPES.lock().unwrap().remove(&(cell_ptr as usize));
+        let cell = unsafe { std::ptr::read(cell_ptr) };
+
+        let index = DESTRUCTED_METATABLE
+            .lock()
+            .unwrap()
+            .expect("destructed metatable not initialized");
+        self.0.push(l);
+        ValueRef::push_index(l, index);
+        ffi::lua_setmetatable(l.0, -2);
+        ffi::lua_pop(l.0, 1);
+
+        Ok(cell.into_inner())
+    }
 }
 
+/// Userdata compares by raw pointer identity (the same Lua userdata value), not by `__eq`. Use
+/// [`Value::equals`] if a metamethod-aware comparison is needed instead.
+impl PartialEq for AnyUserData {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr() == other.ptr()
+    }
+}
+
+impl Eq for AnyUserData {}
+
 impl ObjectLike for AnyUserData {
     fn get<V: FromLua>(&self, l: &lua::State, key: impl ToLua) -> Result<V> {
         Table(self.0.clone()).get_protected(l, key)
@@ -0,0 +1,162 @@
+//! The lock-subsystem-backed analog of [`async_method`](super::async_method): instead of
+//! spawning the future onto the tokio runtime, it's polled in place, once per tick, by a
+//! scheduler that piggybacks on a sibling of the [`lock`](crate::lua::lock) module's own timer.
+//! Useful for futures that are just Rust state machines (no OS thread/tokio task needed) and
+//! for builds with the `tokio` feature disabled.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+use crate::lua::types::{Callback, CallbackResult, MaybeSend};
+use crate::lua::{self, Function, Result, ToLuaMulti, Value, ffi, value_ref::ValueRef};
+use crate::lua::{FromLuaMulti, Thread};
+
+/// Leaked registry index of a Lua function `raw -> function(self, ...)`, mirroring
+/// [`async_method`](super::async_method)'s `ASYNC_WRAP` glue (kept as a separate copy so this
+/// module doesn't depend on the `tokio`-gated one).
+static ASYNC_WRAP: Mutex<Option<i32>> = Mutex::new(None);
+
+// A scheduled call's future, boxed down to "poll it, and if it's done, resume the coroutine
+// that's waiting on it" so the scheduler can hold a single homogeneous queue regardless of
+// each call's own `Args`/`Fut`/`R`.
+type PendingPoll = Box<dyn FnMut(&lua::State, &mut Context<'_>) -> Poll<()> + Send>;
+
+static PENDING: Mutex<Vec<PendingPoll>> = Mutex::new(Vec::new());
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+    fn wake_by_ref(self: &Arc<Self>) {}
+}
+
+// The scheduler re-polls every pending call on every tick regardless of whether anything
+// actually woke it, so a real `Waker` (and the bookkeeping to make one useful) isn't needed.
+fn noop_waker() -> Waker {
+    Waker::from(Arc::new(NoopWaker))
+}
+
+fn schedule(poll: PendingPoll) {
+    PENDING.lock().unwrap().push(poll);
+}
+
+fn run_pending(l: &lua::State) {
+    // Drain into a local batch rather than polling under the lock: a resumed coroutine can
+    // synchronously call back into Lua and register another pending call before this tick
+    // finishes, which would otherwise try to re-lock `PENDING` while it's still held.
+    let mut batch = std::mem::take(&mut *PENDING.lock().unwrap());
+    if batch.is_empty() {
+        return;
+    }
+
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    batch.retain_mut(|poll| (poll)(l, &mut cx).is_pending());
+
+    if !batch.is_empty() {
+        PENDING.lock().unwrap().extend(batch);
+    }
+}
+
+inventory::submit! {
+    crate::open_close::new(
+        0,
+        "userdata_async_lock_methods",
+        |l| {
+            let chunk = l.load_buffer(b"
+                return function(raw)
+                    return function(self, ...)
+                        local ok, result = raw(self, ...)
+                        if not ok then
+                            error(result, 2)
+                        end
+                        return result
+                    end
+                end
+            ", c"ud_async_lock_wrap").expect("failed to load async method wrapper chunk");
+
+            let make_wrapper: Function = chunk
+                .call(l, ())
+                .expect("failed to get async method wrapper generator");
+            *ASYNC_WRAP.lock().unwrap() = Some(make_wrapper.0.leak_index());
+
+            crate::timer::create(
+                &format!("gmodx_async_lock_methods-{}", gmodx_macros::unique_id!()),
+                0,
+                0,
+                run_pending,
+            );
+        },
+        |_| {
+            *ASYNC_WRAP.lock().unwrap() = None;
+            PENDING.lock().unwrap().clear();
+        },
+    )
+}
+
+/// Wraps a raw yield/resume trampoline (see [`trampoline`]) with the shared
+/// `ok, result -> result | error(result)` glue so it behaves like a normal method to Lua.
+pub(crate) fn wrap_async_callback(l: &lua::State, raw: Function) -> Function {
+    let index = ASYNC_WRAP
+        .lock()
+        .unwrap()
+        .expect("async lock method wrapper not initialized");
+    ValueRef::push_index(l, index);
+    let make_wrapper = Function(Value::pop_from_stack(l));
+    make_wrapper
+        .call(l, raw)
+        .expect("failed to wrap async lock method")
+}
+
+/// Builds the raw `Callback` for an async method/function backed by the lock subsystem: parses
+/// `Args` off the stack and calls `f` to get the future, polls it once immediately (so a
+/// future that resolves synchronously never has to yield at all), and otherwise yields the
+/// calling coroutine and hands the future to [`run_pending`]'s scheduler, which re-polls it
+/// once per tick until it resolves.
+pub(crate) fn trampoline<Args, Fut, R>(
+    f: impl Fn(&lua::State, Args) -> Fut + MaybeSend + 'static,
+) -> Callback
+where
+    Args: FromLuaMulti,
+    Fut: Future<Output = Result<R>> + Send + 'static,
+    R: ToLuaMulti + Send + 'static,
+{
+    Box::new(move |l: &lua::State| -> CallbackResult {
+        let nargs = ffi::lua_gettop(l.0);
+        let (args, _) = Args::try_from_stack_multi(l, 1, nargs)?;
+
+        let mut fut: Pin<Box<dyn Future<Output = Result<R>> + Send>> = Box::pin(f(l, args));
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(Ok(value)) => return Ok((true, value).push_to_stack_multi_count(l)),
+            Poll::Ready(Err(err)) => return Err(err.into()),
+            Poll::Pending => {}
+        }
+
+        ffi::lua_pushthread(l.0);
+        let thread = Thread(Value::pop_from_stack(l), l.clone());
+
+        schedule(Box::new(move |l, cx| match fut.as_mut().poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(result) => {
+                let resumed = match result {
+                    Ok(value) => thread.resume_void(l, (true, value)),
+                    Err(err) => thread.resume_void(l, (false, err.to_string())),
+                };
+                if let Err(err) = resumed {
+                    l.error_no_halt_with_stack(&format!(
+                        "failed to resume coroutine after async lock method: {}",
+                        err
+                    ));
+                }
+                Poll::Ready(())
+            }
+        }));
+
+        Ok(ffi::lua_yield(l.0, 0))
+    })
+}
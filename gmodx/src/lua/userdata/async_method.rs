@@ -0,0 +1,95 @@
+use std::future::Future;
+use std::sync::Mutex;
+
+use crate::lua::types::{Callback, CallbackResult, MaybeSend};
+use crate::lua::{self, Function, Result, ToLuaMulti, Value, ffi, value_ref::ValueRef};
+use crate::lua::{FromLuaMulti, Thread};
+use crate::next_tick::next_tick;
+use crate::tokio_tasks;
+
+/// Leaked registry index of a Lua function `raw -> function(self, ...)` that turns a raw
+/// yield/resume trampoline into a method which raises a normal Lua error instead of just
+/// returning `false, err`, mirroring how a synchronous method's `Err` would `lua_error`.
+static ASYNC_WRAP: Mutex<Option<i32>> = Mutex::new(None);
+
+inventory::submit! {
+    crate::open_close::new(
+        0,
+        "userdata_async_methods",
+        |l| {
+            let chunk = l.load_buffer(b"
+                return function(raw)
+                    return function(self, ...)
+                        local ok, result = raw(self, ...)
+                        if not ok then
+                            error(result, 2)
+                        end
+                        return result
+                    end
+                end
+            ", c"ud_async_wrap").expect("failed to load async method wrapper chunk");
+
+            let make_wrapper: Function = chunk
+                .call(l, ())
+                .expect("failed to get async method wrapper generator");
+            *ASYNC_WRAP.lock().unwrap() = Some(make_wrapper.0.leak_index());
+        },
+        |_| {
+            *ASYNC_WRAP.lock().unwrap() = None;
+        },
+    )
+}
+
+/// Wraps a raw yield/resume trampoline (see [`trampoline`]) with the shared
+/// `ok, result -> result | error(result)` glue so it behaves like a normal method to Lua.
+pub(crate) fn wrap_async_callback(l: &lua::State, raw: Function) -> Function {
+    let index = ASYNC_WRAP
+        .lock()
+        .unwrap()
+        .expect("async method wrapper not initialized");
+    ValueRef::push_index(l, index);
+    let make_wrapper = Function(Value::pop_from_stack(l));
+    make_wrapper
+        .call(l, raw)
+        .expect("failed to wrap async method")
+}
+
+/// Builds the raw `Callback` for an async method/function: parses `Args` off the stack,
+/// calls `f` to get the future, spawns it onto the tokio task runtime, yields the calling
+/// coroutine, and schedules a resume (via the next-tick queue) once the future resolves.
+pub(crate) fn trampoline<Args, Fut, R>(
+    f: impl Fn(&lua::State, Args) -> Fut + MaybeSend + 'static,
+) -> Callback
+where
+    Args: FromLuaMulti,
+    Fut: Future<Output = Result<R>> + Send + 'static,
+    R: ToLuaMulti + Send + 'static,
+{
+    Box::new(move |l: &lua::State| -> CallbackResult {
+        let nargs = ffi::lua_gettop(l.0);
+        let (args, _) = Args::try_from_stack_multi(l, 1, nargs)?;
+
+        let fut = f(l, args);
+
+        ffi::lua_pushthread(l.0);
+        let thread = Thread(Value::pop_from_stack(l), l.clone());
+
+        tokio_tasks::spawn(async move {
+            let result = fut.await;
+            next_tick(move |l| {
+                let resumed = match result {
+                    Ok(value) => thread.resume_void(l, (true, value)),
+                    Err(err) => thread.resume_void(l, (false, err.to_string())),
+                };
+                if let Err(err) = resumed {
+                    l.error_no_halt_with_stack(&format!(
+                        "failed to resume coroutine after async method: {}",
+                        err
+                    ));
+                }
+            });
+        });
+
+        Ok(ffi::lua_yield(l.0, 0))
+    })
+}
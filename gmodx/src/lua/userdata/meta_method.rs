@@ -0,0 +1,47 @@
+use std::ffi::CStr;
+
+/// The standard Lua 5.1/LuaJIT metamethods, for use with [`MethodsBuilder::add_meta`]
+/// (`MethodsBuilder` is in `super`) instead of a stringly-typed `&'static CStr` name, so a
+/// typo doesn't silently register a dead metamethod.
+///
+/// `Index`, `NewIndex` and `Gc` are deliberately not included: those three are wired up by
+/// `create_userdata` itself (the shared store table and the `Drop`-based finalizer), and
+/// having no variant here, they can't be passed to [`MethodsBuilder::add_meta`] at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MetaMethod {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+    Unm,
+    Concat,
+    Len,
+    Eq,
+    Lt,
+    Le,
+    Call,
+    ToString,
+}
+
+impl MetaMethod {
+    pub(crate) fn name(self) -> &'static CStr {
+        match self {
+            MetaMethod::Add => c"__add",
+            MetaMethod::Sub => c"__sub",
+            MetaMethod::Mul => c"__mul",
+            MetaMethod::Div => c"__div",
+            MetaMethod::Mod => c"__mod",
+            MetaMethod::Pow => c"__pow",
+            MetaMethod::Unm => c"__unm",
+            MetaMethod::Concat => c"__concat",
+            MetaMethod::Len => c"__len",
+            MetaMethod::Eq => c"__eq",
+            MetaMethod::Lt => c"__lt",
+            MetaMethod::Le => c"__le",
+            MetaMethod::Call => c"__call",
+            MetaMethod::ToString => c"__tostring",
+        }
+    }
+}
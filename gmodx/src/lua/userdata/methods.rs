@@ -1,15 +1,39 @@
 use std::ffi::CStr;
 
-use crate::lua::{Function, function::IntoLuaFunction};
+use crate::lua::types::{Callback, MaybeSend};
+use crate::lua::userdata::meta_method::MetaMethod;
+use crate::lua::{
+    self, AnyUserData, FromLua, FromLuaMulti, Result, ToLua, ToLuaMulti, UserData, UserDataRef,
+    function::IntoLuaFunction, userdata::async_lock_method,
+};
 
-type Methods = Vec<(&'static CStr, Function)>;
+#[cfg(feature = "tokio")]
+use crate::lua::userdata::async_method;
+#[cfg(all(feature = "tokio", feature = "send"))]
+use crate::lua::userdata::ScopedUserDataRef;
+
+type Methods = Vec<(&'static CStr, lua::Function)>;
 
 #[derive(Default)]
-pub struct MethodsBuilder(pub(crate) Methods);
+pub struct MethodsBuilder {
+    pub(crate) methods: Methods,
+    pub(crate) getters: Methods,
+    pub(crate) setters: Methods,
+    #[cfg(feature = "tokio")]
+    pub(crate) async_methods: Vec<(&'static CStr, Callback)>,
+    pub(crate) lock_async_methods: Vec<(&'static CStr, Callback)>,
+}
 
 impl MethodsBuilder {
     pub(crate) fn new() -> Self {
-        Self(Vec::new())
+        Self {
+            methods: Vec::new(),
+            getters: Vec::new(),
+            setters: Vec::new(),
+            #[cfg(feature = "tokio")]
+            async_methods: Vec::new(),
+            lock_async_methods: Vec::new(),
+        }
     }
 
     pub fn add<Marker>(
@@ -18,7 +42,264 @@ impl MethodsBuilder {
         func: impl IntoLuaFunction<Marker>,
     ) -> &mut Self {
         let callback = func.into_function();
-        self.0.push((name, callback));
+        self.methods.push((name, callback));
+        self
+    }
+
+    /// Registers a metamethod from the typed [`MetaMethod`] enum rather than a raw `CStr`
+    /// name, so a typo can't silently produce a dead metamethod. Panics if `meta` was already
+    /// registered on this builder (most metamethods only make sense installed once).
+    ///
+    /// `__index`/`__newindex`/`__gc` aren't reachable through [`MetaMethod`] at all: those are
+    /// wired up by `create_userdata` itself, and letting a type override them here would
+    /// silently break the shared store/GC machinery.
+    pub fn add_meta<Marker>(
+        &mut self,
+        meta: MetaMethod,
+        func: impl IntoLuaFunction<Marker>,
+    ) -> &mut Self {
+        let name = meta.name();
+        assert!(
+            !self.methods.iter().any(|(n, _)| *n == name),
+            "{meta:?} ({}) already registered",
+            name.to_string_lossy()
+        );
+        self.add(name, func)
+    }
+
+    /// Registers a field getter, so that `obj.<name>` in Lua runs `f(&T)` and returns its result.
+    pub fn add_field_method_get<T, R, F>(&mut self, name: &'static CStr, f: F) -> &mut Self
+    where
+        T: UserData + 'static,
+        R: ToLua,
+        F: Fn(&T) -> R + MaybeSend + 'static,
+    {
+        let callback = (move |_: &lua::State, ud: UserDataRef<T>| -> R { f(&ud.borrow()) })
+            .into_function();
+        self.getters.push((name, callback));
+        self
+    }
+
+    /// Registers a field setter, so that `obj.<name> = value` in Lua runs `f(&mut T, value)`.
+    pub fn add_field_method_set<T, V, F>(&mut self, name: &'static CStr, f: F) -> &mut Self
+    where
+        T: UserData + 'static,
+        V: FromLua,
+        F: Fn(&mut T, V) + MaybeSend + 'static,
+    {
+        let callback = (move |_: &lua::State, ud: UserDataRef<T>, value: V| {
+            f(&mut ud.borrow_mut(), value);
+        })
+        .into_function();
+        self.setters.push((name, callback));
+        self
+    }
+
+    /// Like [`Self::add_field_method_get`], but hands the closure the raw [`AnyUserData`]
+    /// instead of borrowing a typed `T`.
+    pub fn add_field_function_get<R, F>(&mut self, name: &'static CStr, f: F) -> &mut Self
+    where
+        R: ToLua,
+        F: Fn(&lua::State, AnyUserData) -> Result<R> + MaybeSend + 'static,
+    {
+        let callback = f.into_function();
+        self.getters.push((name, callback));
+        self
+    }
+
+    /// Like [`Self::add_field_method_set`], but hands the closure the raw [`AnyUserData`]
+    /// instead of borrowing a typed `T`.
+    pub fn add_field_function_set<V, F>(&mut self, name: &'static CStr, f: F) -> &mut Self
+    where
+        V: FromLua,
+        F: Fn(&lua::State, AnyUserData, V) -> Result<()> + MaybeSend + 'static,
+    {
+        let callback = f.into_function();
+        self.setters.push((name, callback));
+        self
+    }
+
+    /// Registers an async method. `f` is called synchronously (so it can only borrow `T`
+    /// for as long as it takes to build the future) and must return a future resolving to
+    /// a [`Result`]. When Lua calls `obj:<name>(...)` from inside a coroutine, the future is
+    /// spawned onto the tokio task runtime, the calling coroutine is suspended via
+    /// `lua_yield`, and it is resumed with the future's result (or the error) once the
+    /// future completes.
+    #[cfg(feature = "tokio")]
+    pub fn add_async_method<T, A, Fut, R>(
+        &mut self,
+        name: &'static CStr,
+        f: impl Fn(&T, A) -> Fut + MaybeSend + 'static,
+    ) -> &mut Self
+    where
+        T: UserData + 'static,
+        A: FromLuaMulti,
+        Fut: std::future::Future<Output = Result<R>> + Send + 'static,
+        R: ToLuaMulti + Send + 'static,
+    {
+        let callback = async_method::trampoline(
+            move |_: &lua::State, (ud, args): (UserDataRef<T>, A)| f(&ud.borrow(), args),
+        );
+        self.async_methods.push((name, callback));
+        self
+    }
+
+    /// Like [`Self::add_async_method`], but `f` borrows `T` mutably while building the future.
+    #[cfg(feature = "tokio")]
+    pub fn add_async_method_mut<T, A, Fut, R>(
+        &mut self,
+        name: &'static CStr,
+        f: impl Fn(&mut T, A) -> Fut + MaybeSend + 'static,
+    ) -> &mut Self
+    where
+        T: UserData + 'static,
+        A: FromLuaMulti,
+        Fut: std::future::Future<Output = Result<R>> + Send + 'static,
+        R: ToLuaMulti + Send + 'static,
+    {
+        let callback = async_method::trampoline(
+            move |_: &lua::State, (ud, args): (UserDataRef<T>, A)| f(&mut ud.borrow_mut(), args),
+        );
+        self.async_methods.push((name, callback));
+        self
+    }
+
+    /// Like [`Self::add_async_method`], but hands the closure the raw [`AnyUserData`]
+    /// instead of borrowing a typed `T`.
+    #[cfg(feature = "tokio")]
+    pub fn add_async_function<A, Fut, R>(
+        &mut self,
+        name: &'static CStr,
+        f: impl Fn(AnyUserData, A) -> Fut + MaybeSend + 'static,
+    ) -> &mut Self
+    where
+        A: FromLuaMulti,
+        Fut: std::future::Future<Output = Result<R>> + Send + 'static,
+        R: ToLuaMulti + Send + 'static,
+    {
+        let callback = async_method::trampoline(
+            move |_: &lua::State, (any, args): (AnyUserData, A)| f(any, args),
+        );
+        self.async_methods.push((name, callback));
+        self
+    }
+
+    /// Registers an async method backed by the [`lock`](crate::lua::lock) subsystem rather
+    /// than tokio: `f` is handed a [`UserDataRef<T>`] it owns for the lifetime of the future,
+    /// instead of a borrowed `&T`, so it can call [`UserDataRef::borrow`] transiently on each
+    /// poll without ever holding the guard across a `.await`. When Lua calls `obj:<name>(...)`
+    /// from inside a coroutine, the future is polled once immediately; if it doesn't resolve
+    /// synchronously, the coroutine is suspended via `lua_yield` and the future is re-polled
+    /// once per tick (while `LUA_LOCK` is held) until it resolves. Works without the `tokio`
+    /// feature.
+    pub fn add_async<T, A, Fut, R>(
+        &mut self,
+        name: &'static CStr,
+        f: impl Fn(UserDataRef<T>, A) -> Fut + MaybeSend + 'static,
+    ) -> &mut Self
+    where
+        T: UserData + 'static,
+        A: FromLuaMulti,
+        Fut: std::future::Future<Output = Result<R>> + Send + 'static,
+        R: ToLuaMulti + Send + 'static,
+    {
+        let callback = async_lock_method::trampoline(
+            move |_: &lua::State, (ud, args): (UserDataRef<T>, A)| f(ud, args),
+        );
+        self.lock_async_methods.push((name, callback));
+        self
+    }
+
+    /// Like [`Self::add_async`], named to mirror [`Self::add_async_method_mut`] for methods
+    /// that mutate `T` via [`UserDataRef::borrow_mut`] — `f` still owns the handle rather than
+    /// a borrowed `&mut T`, since that borrow can only be taken transiently within a single
+    /// poll, never across the yield points between them.
+    pub fn add_async_mut<T, A, Fut, R>(
+        &mut self,
+        name: &'static CStr,
+        f: impl Fn(UserDataRef<T>, A) -> Fut + MaybeSend + 'static,
+    ) -> &mut Self
+    where
+        T: UserData + 'static,
+        A: FromLuaMulti,
+        Fut: std::future::Future<Output = Result<R>> + Send + 'static,
+        R: ToLuaMulti + Send + 'static,
+    {
+        let callback = async_lock_method::trampoline(
+            move |_: &lua::State, (ud, args): (UserDataRef<T>, A)| f(ud, args),
+        );
+        self.lock_async_methods.push((name, callback));
+        self
+    }
+
+    /// Registers an async method on [`ScopedUserData`](crate::lua::ScopedUserData)/
+    /// [`ScopedUserDataRef`], tokio-backed like [`Self::add_async_method`] but handing `f` the
+    /// `ScopedUserDataRef<T>` handle itself instead of a transient `&T`. Unlike
+    /// [`Self::add_async_method`] (which must borrow synchronously, before the future exists,
+    /// because a plain `UserDataRef<T>`'s `RefCell` guard can't be held across an `.await`),
+    /// `f`'s future can call [`ScopedUserDataRef::lock_async`] to acquire its `Mutex<Option<T>>`
+    /// asynchronously — so a method that has to wait on contention doesn't block the reentrant
+    /// `LUA_LOCK` while it waits. Only meaningful with the `send` feature, where
+    /// `ScopedUserDataRef` is backed by an `Arc<Mutex<_>>` rather than an `Rc<RefCell<_>>`.
+    #[cfg(all(feature = "tokio", feature = "send"))]
+    pub fn add_async_scoped<T, A, Fut, R>(
+        &mut self,
+        name: &'static CStr,
+        f: impl Fn(ScopedUserDataRef<T>, A) -> Fut + MaybeSend + 'static,
+    ) -> &mut Self
+    where
+        T: UserData + 'static,
+        A: FromLuaMulti,
+        Fut: std::future::Future<Output = Result<R>> + Send + 'static,
+        R: ToLuaMulti + Send + 'static,
+    {
+        let callback = async_method::trampoline(
+            move |_: &lua::State, (ud, args): (ScopedUserDataRef<T>, A)| f(ud, args),
+        );
+        self.async_methods.push((name, callback));
+        self
+    }
+
+    /// Like [`Self::add_async_scoped`], named to mirror [`Self::add_async_method_mut`] for
+    /// methods that mutate `T` via the guard's `as_mut()`. `f` still owns the
+    /// `ScopedUserDataRef<T>` handle rather than a borrowed `&mut T`, for the same reason as
+    /// [`Self::add_async_scoped`].
+    #[cfg(all(feature = "tokio", feature = "send"))]
+    pub fn add_async_scoped_mut<T, A, Fut, R>(
+        &mut self,
+        name: &'static CStr,
+        f: impl Fn(ScopedUserDataRef<T>, A) -> Fut + MaybeSend + 'static,
+    ) -> &mut Self
+    where
+        T: UserData + 'static,
+        A: FromLuaMulti,
+        Fut: std::future::Future<Output = Result<R>> + Send + 'static,
+        R: ToLuaMulti + Send + 'static,
+    {
+        let callback = async_method::trampoline(
+            move |_: &lua::State, (ud, args): (ScopedUserDataRef<T>, A)| f(ud, args),
+        );
+        self.async_methods.push((name, callback));
+        self
+    }
+
+    /// Like [`Self::add_async`], but hands the closure the raw [`AnyUserData`] instead of a
+    /// typed [`UserDataRef<T>`] — meant for meta-methods (e.g. `__add`), where neither operand
+    /// is guaranteed to be `T`.
+    pub fn add_async_meta<A, Fut, R>(
+        &mut self,
+        name: &'static CStr,
+        f: impl Fn(AnyUserData, A) -> Fut + MaybeSend + 'static,
+    ) -> &mut Self
+    where
+        A: FromLuaMulti,
+        Fut: std::future::Future<Output = Result<R>> + Send + 'static,
+        R: ToLuaMulti + Send + 'static,
+    {
+        let callback = async_lock_method::trampoline(
+            move |_: &lua::State, (any, args): (AnyUserData, A)| f(any, args),
+        );
+        self.lock_async_methods.push((name, callback));
         self
     }
 }
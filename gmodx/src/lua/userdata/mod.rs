@@ -7,14 +7,29 @@ use rustc_hash::{FxBuildHasher, FxHashMap};
 mod methods;
 pub use methods::MethodsBuilder;
 
+/// Alias mirroring mlua's `UserDataFields`, used by [`UserData::fields`]. Field getters/setters
+/// just end up in the same [`MethodsBuilder`] as `methods`/`meta_methods` register into (they
+/// already share the `__gmodx_getters`/`__gmodx_setters` dispatch tables), so this is the same
+/// builder under a name that matches what it's for at each call site.
+pub use methods::MethodsBuilder as FieldsBuilder;
+
+#[cfg(feature = "tokio")]
+pub(crate) mod async_method;
+pub(crate) mod async_lock_method;
+
 mod any;
 pub use any::AnyUserData;
 
+mod meta_method;
+pub use meta_method::MetaMethod;
+
 mod r#ref;
 pub use r#ref::UserDataRef;
 
 mod scoped;
 pub use scoped::{ScopedUserData, ScopedUserDataRef};
+#[cfg(feature = "send")]
+pub use scoped::{ScopedUserDataRw, ScopedUserDataRwRef};
 
 use crate::lua::value_ref::ValueRef;
 use crate::lua::{self, ffi::lua_State};
@@ -41,6 +56,14 @@ inventory::submit! {
                 local getmetatable = getmetatable
                 local STORE = setmetatable({}, { __mode = 'k' })
                 local function __index(self, k)
+                    local mt = getmetatable(self)
+                    local getters = mt and rawget(mt, '__gmodx_getters')
+                    if getters then
+                        local getter = getters[k]
+                        if getter ~= nil then
+                            return getter(self)
+                        end
+                    end
                     local store = STORE[self]
                     if store then
                         local v = store[k]
@@ -48,9 +71,18 @@ inventory::submit! {
                             return v
                         end
                     end
-                    return getmetatable(self)[k]
+                    return mt[k]
                 end
                 local function __newindex(self, k, v)
+                    local mt = getmetatable(self)
+                    local setters = mt and rawget(mt, '__gmodx_setters')
+                    if setters then
+                        local setter = setters[k]
+                        if setter ~= nil then
+                            setter(self, v)
+                            return
+                        end
+                    end
                     local store = STORE[self]
                     if not store then
                         STORE[self] = {
@@ -117,6 +149,13 @@ pub trait UserData {
     fn meta_methods(_: &mut MethodsBuilder) {}
     fn methods(_: &mut MethodsBuilder) {}
 
+    /// Registers computed properties, so that `ud.<name>` and `ud.<name> = value` run Rust
+    /// code instead of falling through to the plain-value store. Equivalent to calling
+    /// [`FieldsBuilder::add_field_method_get`]/[`add_field_method_set`](FieldsBuilder::add_field_method_set)
+    /// from [`Self::methods`] directly; this hook exists purely so field declarations can be
+    /// grouped separately from callable methods, mirroring mlua's `add_fields`.
+    fn fields(_: &mut FieldsBuilder) {}
+
     #[must_use]
     fn name() -> &'static str {
         type_name::<Self>()
@@ -149,16 +188,20 @@ fn push_methods_table<T: UserData>(l: &lua::State) {
         0
     }
 
-    if !ffi::luaL_newmetatable(l.0, unique_id::<T>().as_ptr()) {
+    // Use `T::unique_id()` (not the free function) so that types which forward
+    // `unique_id()` to another type (e.g. `impl UserData for XRc<T>`) share its
+    // metatable instead of getting a separate one keyed on their own `TypeId`.
+    if !ffi::luaL_newmetatable(l.0, T::unique_id().as_ptr()) {
         return;
     }
 
     let mut mb = MethodsBuilder::new();
     T::methods(&mut mb);
     T::meta_methods(&mut mb);
+    T::fields(&mut mb);
 
     let mut has_tostring = false;
-    for (name, func) in mb.0 {
+    for (name, func) in mb.methods {
         assert!(
             name != c"__gc",
             "{}: use Drop instead of __gc",
@@ -169,6 +212,11 @@ fn push_methods_table<T: UserData>(l: &lua::State) {
             "{}: __index/__newindex reserved",
             type_name::<T>()
         );
+        assert!(
+            name != c"__gmodx_getters" && name != c"__gmodx_setters",
+            "{}: __gmodx_getters/__gmodx_setters reserved",
+            type_name::<T>()
+        );
         has_tostring |= name == c"__tostring";
 
         func.push_to_stack(l);
@@ -180,6 +228,49 @@ fn push_methods_table<T: UserData>(l: &lua::State) {
         ffi::lua_setfield(l.0, -2, c"__tostring".as_ptr());
     }
 
+    if !mb.getters.is_empty() {
+        let getters = l.create_table_with_capacity(0, mb.getters.len() as i32);
+        for (name, func) in mb.getters {
+            getters.raw_set(l, name, func);
+        }
+        getters.push_to_stack(l);
+        ffi::lua_setfield(l.0, -2, c"__gmodx_getters".as_ptr());
+    }
+
+    if !mb.setters.is_empty() {
+        let setters = l.create_table_with_capacity(0, mb.setters.len() as i32);
+        for (name, func) in mb.setters {
+            setters.raw_set(l, name, func);
+        }
+        setters.push_to_stack(l);
+        ffi::lua_setfield(l.0, -2, c"__gmodx_setters".as_ptr());
+    }
+
+    #[cfg(feature = "tokio")]
+    for (name, raw) in mb.async_methods {
+        assert!(
+            name != c"__gc" && name != c"__index" && name != c"__newindex",
+            "{}: __gc/__index/__newindex reserved",
+            type_name::<T>()
+        );
+        let raw_fn = l.create_function_impl(raw);
+        let wrapped = async_method::wrap_async_callback(l, raw_fn);
+        wrapped.push_to_stack(l);
+        ffi::lua_setfield(l.0, -2, name.as_ptr());
+    }
+
+    for (name, raw) in mb.lock_async_methods {
+        assert!(
+            name != c"__gc" && name != c"__index" && name != c"__newindex",
+            "{}: __gc/__index/__newindex reserved",
+            type_name::<T>()
+        );
+        let raw_fn = l.create_function_impl(raw);
+        let wrapped = async_lock_method::wrap_async_callback(l, raw_fn);
+        wrapped.push_to_stack(l);
+        ffi::lua_setfield(l.0, -2, name.as_ptr());
+    }
+
     let (__index, __newindex) = get_ud_metamethods();
     ValueRef::push_index(l, __index);
     ffi::lua_setfield(l.0, -2, c"__index".as_ptr());
@@ -217,3 +308,86 @@ impl<T: UserData + 'static> ToLua for T {
         l.create_userdata(self).push_to_stack(l);
     }
 }
+
+/// Shared-ownership userdata: an `XRc<T>` can be handed to Lua just like a `T`.
+/// Every method/meta-method/field is forwarded to `T`, and cloning the `XRc` before
+/// pushing it lets multiple Lua userdata values alias the same underlying Rust object.
+/// The pointer only gives shared access, so anything that needs `&mut T` (field setters,
+/// `_mut` methods, `UserDataRef::borrow_mut`) fails on such a value with a "shared" error.
+impl<T: UserData + 'static> UserData for crate::sync::XRc<T> {
+    fn meta_methods(mb: &mut MethodsBuilder) {
+        T::meta_methods(mb);
+    }
+
+    fn methods(mb: &mut MethodsBuilder) {
+        T::methods(mb);
+    }
+
+    fn fields(mb: &mut FieldsBuilder) {
+        T::fields(mb);
+    }
+
+    fn name() -> &'static str {
+        T::name()
+    }
+
+    fn unique_id() -> &'static CStr {
+        T::unique_id()
+    }
+}
+
+// `XRc<T>` is already `Rc<T>` (without the `send` feature) or `Arc<T>` (with it), so only the
+// other one needs its own impl here — giving it one unconditionally would conflict with the
+// `XRc<T>` impl above for whichever of the two `XRc` currently aliases to.
+
+/// Shared-ownership userdata via a plain [`std::rc::Rc`], for addons that want `Rc<T>`
+/// specifically regardless of which allocator `XRc` is wired to. See the `XRc<T>` impl above
+/// for what gets forwarded and why.
+#[cfg(feature = "send")]
+impl<T: UserData + 'static> UserData for std::rc::Rc<T> {
+    fn meta_methods(mb: &mut MethodsBuilder) {
+        T::meta_methods(mb);
+    }
+
+    fn methods(mb: &mut MethodsBuilder) {
+        T::methods(mb);
+    }
+
+    fn fields(mb: &mut FieldsBuilder) {
+        T::fields(mb);
+    }
+
+    fn name() -> &'static str {
+        T::name()
+    }
+
+    fn unique_id() -> &'static CStr {
+        T::unique_id()
+    }
+}
+
+/// Shared-ownership userdata via a plain [`std::sync::Arc`], for addons that want `Arc<T>`
+/// specifically regardless of which allocator `XRc` is wired to. See the `XRc<T>` impl above
+/// for what gets forwarded and why.
+#[cfg(not(feature = "send"))]
+impl<T: UserData + 'static> UserData for std::sync::Arc<T> {
+    fn meta_methods(mb: &mut MethodsBuilder) {
+        T::meta_methods(mb);
+    }
+
+    fn methods(mb: &mut MethodsBuilder) {
+        T::methods(mb);
+    }
+
+    fn fields(mb: &mut FieldsBuilder) {
+        T::fields(mb);
+    }
+
+    fn name() -> &'static str {
+        T::name()
+    }
+
+    fn unique_id() -> &'static CStr {
+        T::unique_id()
+    }
+}
@@ -4,6 +4,29 @@ use std::{
 };
 
 use crate::lua::{self, AnyUserData, Error, FromLua, Result, ToLua, UserData, Value};
+use crate::sync::XRc;
+
+/// The sibling smart pointer `XRc<T>` does *not* currently alias to: `Rc<T>` when `send` is on
+/// (`XRc` = `Arc`), `Arc<T>` when it's off (`XRc` = `Rc`). Both get their own `UserData` impl
+/// (see `userdata/mod.rs`) so a plugin can push a pre-existing `Rc`/`Arc` it already holds
+/// without going through `XRc`; `cast_to` below needs to recognize the resulting box shape too.
+#[cfg(feature = "send")]
+type OtherRc<T> = std::rc::Rc<T>;
+#[cfg(not(feature = "send"))]
+type OtherRc<T> = std::sync::Arc<T>;
+
+/// Which concrete type is stored in the Lua userdata box this reference points into.
+#[derive(Clone, Copy, Debug)]
+enum Storage {
+    /// The box holds `RefCell<T>` directly.
+    Direct,
+    /// The box holds `RefCell<XRc<T>>` (pushed via `impl UserData for XRc<T>`),
+    /// so it may be aliased by other `UserDataRef<T>`s cloned from the same `XRc`.
+    Shared,
+    /// The box holds `RefCell<OtherRc<T>>` (pushed via the sibling `Rc<T>`/`Arc<T>` impl),
+    /// for a value pushed through the smart pointer `XRc` doesn't currently alias to.
+    SharedOther,
+}
 
 /// The 'static bound is needed to ensure the userdata lives long enough
 #[derive(Debug)]
@@ -11,6 +34,7 @@ pub struct UserDataRef<T: UserData + 'static> {
     /// Pointer to the userdata in Lua
     pub(crate) ptr: *const c_void,
     pub(crate) any: AnyUserData,
+    storage: Storage,
     pub(crate) _marker: std::marker::PhantomData<T>,
 }
 
@@ -19,6 +43,7 @@ impl<T: UserData> Clone for UserDataRef<T> {
         Self {
             ptr: self.ptr,
             any: self.any.clone(),
+            storage: self.storage,
             _marker: std::marker::PhantomData,
         }
     }
@@ -26,36 +51,78 @@ impl<T: UserData> Clone for UserDataRef<T> {
 
 impl<T: UserData> UserDataRef<T> {
     #[inline]
-    const fn downcast(&self) -> &RefCell<T> {
-        // SAFETY: The pointer is valid as long as the inner value is alive.
-        // SAFETY: We type check before initializing UserDataRef.
+    const fn direct(&self) -> &RefCell<T> {
+        // SAFETY: only reached when `storage` is `Storage::Direct`, meaning the box
+        // holds `RefCell<T>` exactly (checked in `AnyUserData::cast_to`).
         unsafe { &*(self.ptr.cast::<RefCell<T>>()) }
     }
 
+    #[inline]
+    const fn shared(&self) -> &RefCell<XRc<T>> {
+        // SAFETY: only reached when `storage` is `Storage::Shared`, meaning the box
+        // holds `RefCell<XRc<T>>` exactly (checked in `AnyUserData::cast_to`).
+        unsafe { &*(self.ptr.cast::<RefCell<XRc<T>>>()) }
+    }
+
+    #[inline]
+    const fn shared_other(&self) -> &RefCell<OtherRc<T>> {
+        // SAFETY: only reached when `storage` is `Storage::SharedOther`, meaning the box
+        // holds `RefCell<OtherRc<T>>` exactly (checked in `AnyUserData::cast_to`).
+        unsafe { &*(self.ptr.cast::<RefCell<OtherRc<T>>>()) }
+    }
+
     #[must_use]
     #[inline]
     pub fn borrow(&self) -> Ref<'_, T> {
-        self.downcast().borrow()
+        match self.storage {
+            Storage::Direct => self.direct().borrow(),
+            Storage::Shared => Ref::map(self.shared().borrow(), |rc| &**rc),
+            Storage::SharedOther => Ref::map(self.shared_other().borrow(), |rc| &**rc),
+        }
     }
 
     #[must_use]
     #[inline]
     pub fn borrow_mut(&self) -> RefMut<'_, T> {
-        self.downcast().borrow_mut()
+        match self.storage {
+            Storage::Direct => self.direct().borrow_mut(),
+            Storage::Shared | Storage::SharedOther => {
+                panic!("cannot mutably borrow '{}': it is shared", T::name())
+            }
+        }
     }
 
     #[inline]
     pub fn try_borrow(&self) -> Result<Ref<'_, T>> {
-        self.downcast()
-            .try_borrow()
-            .map_err(|err| Error::Message(format!("cannot borrow '{}': {}", T::name(), err)))
+        let to_err = |err: std::cell::BorrowError| {
+            Error::Message(format!("cannot borrow '{}': {}", T::name(), err))
+        };
+        match self.storage {
+            Storage::Direct => self.direct().try_borrow().map_err(to_err),
+            Storage::Shared => self
+                .shared()
+                .try_borrow()
+                .map_err(to_err)
+                .map(|rc| Ref::map(rc, |rc| &**rc)),
+            Storage::SharedOther => self
+                .shared_other()
+                .try_borrow()
+                .map_err(to_err)
+                .map(|rc| Ref::map(rc, |rc| &**rc)),
+        }
     }
 
     #[inline]
     pub fn try_borrow_mut(&self) -> Result<RefMut<'_, T>> {
-        self.downcast().try_borrow_mut().map_err(|err| {
-            Error::Message(format!("cannot borrow '{}' mutably: {}", T::name(), err))
-        })
+        match self.storage {
+            Storage::Direct => self.direct().try_borrow_mut().map_err(|err| {
+                Error::Message(format!("cannot borrow '{}' mutably: {}", T::name(), err))
+            }),
+            Storage::Shared | Storage::SharedOther => Err(Error::Message(format!(
+                "cannot borrow '{}' mutably: it is shared",
+                T::name()
+            ))),
+        }
     }
 
     #[must_use]
@@ -110,14 +177,31 @@ impl AnyUserData {
     #[must_use]
     #[inline]
     pub fn cast_to<T: UserData>(self, l: &lua::State) -> Option<UserDataRef<T>> {
-        if !self.is::<RefCell<T>>(l) {
-            return None;
+        if self.is::<RefCell<T>>(l) {
+            return Some(UserDataRef {
+                ptr: self.ptr(),
+                any: self,
+                storage: Storage::Direct,
+                _marker: std::marker::PhantomData,
+            });
         }
-        Some(UserDataRef {
-            ptr: self.ptr(),
-            any: self,
-            _marker: std::marker::PhantomData,
-        })
+        if self.is::<RefCell<XRc<T>>>(l) {
+            return Some(UserDataRef {
+                ptr: self.ptr(),
+                any: self,
+                storage: Storage::Shared,
+                _marker: std::marker::PhantomData,
+            });
+        }
+        if self.is::<RefCell<OtherRc<T>>>(l) {
+            return Some(UserDataRef {
+                ptr: self.ptr(),
+                any: self,
+                storage: Storage::SharedOther,
+                _marker: std::marker::PhantomData,
+            });
+        }
+        None
     }
 }
 
@@ -127,6 +211,7 @@ impl lua::State {
         UserDataRef {
             ptr,
             any,
+            storage: Storage::Direct,
             _marker: std::marker::PhantomData,
         }
     }
@@ -195,3 +195,179 @@ impl AnyUserData {
         })
     }
 }
+
+// `RwLock`-backed sibling of `ScopedUserDataRef`/`create_scoped_userdata`: only worth having
+// under the `send` feature, since without it `UserDataStorage<T>` is already a single-threaded
+// `Rc<RefCell<Option<T>>>` with no reader/writer contention to relieve in the first place.
+#[cfg(feature = "send")]
+type UserDataStorageRw<T> = Arc<xutex::RwLock<Option<T>>>;
+
+#[cfg(feature = "send")]
+pub struct ScopedUserDataRwRef<T: UserData> {
+    /// Pointer to the userdata in Lua
+    pub(crate) ptr: usize,
+    pub(crate) value: UserDataStorageRw<T>,
+    pub(crate) any: AnyUserData,
+}
+
+#[cfg(feature = "send")]
+impl<T: UserData> Clone for ScopedUserDataRwRef<T> {
+    fn clone(&self) -> Self {
+        Self {
+            ptr: self.ptr,
+            value: self.value.clone(),
+            any: self.any.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "send")]
+impl<T: UserData> ScopedUserDataRwRef<T> {
+    #[must_use]
+    #[inline]
+    pub const fn as_any(&self) -> &AnyUserData {
+        &self.any
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn into_any(self) -> AnyUserData {
+        self.any
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn inner(self) -> UserDataStorageRw<T> {
+        self.value
+    }
+
+    #[inline]
+    pub fn read(&self) -> xutex::RwLockReadGuard<'_, Option<T>> {
+        self.value.read()
+    }
+
+    pub async fn read_async(&self) -> xutex::RwLockReadGuard<'_, Option<T>> {
+        self.value.read_async().await
+    }
+
+    #[inline]
+    pub fn write(&self) -> xutex::RwLockWriteGuard<'_, Option<T>> {
+        self.value.write()
+    }
+
+    pub async fn write_async(&self) -> xutex::RwLockWriteGuard<'_, Option<T>> {
+        self.value.write_async().await
+    }
+}
+
+#[cfg(feature = "send")]
+impl<T: UserData> ToLua for ScopedUserDataRwRef<T> {
+    fn push_to_stack(self, l: &lua::State) {
+        self.any.push_to_stack(l);
+    }
+
+    fn to_value(self, l: &lua::State) -> Value {
+        self.any.to_value(l)
+    }
+}
+
+#[cfg(feature = "send")]
+impl<T: UserData> ToLua for &ScopedUserDataRwRef<T> {
+    fn push_to_stack(self, l: &lua::State) {
+        (&self.any).push_to_stack(l);
+    }
+
+    fn to_value(self, _: &lua::State) -> Value {
+        self.any.0.clone()
+    }
+}
+
+#[cfg(feature = "send")]
+impl<T: UserData> FromLua for ScopedUserDataRwRef<T> {
+    fn try_from_stack(l: &lua::State, index: i32) -> lua::Result<Self> {
+        let name = T::name();
+        let any = AnyUserData::from_stack_with_type(l, index, name)?;
+        any.scoped_cast_to_rw::<T>(l)
+            .ok_or_else(|| l.type_error(index, name))
+    }
+}
+
+#[cfg(feature = "send")]
+impl<T: UserData> From<ScopedUserDataRwRef<T>> for AnyUserData {
+    fn from(udref: ScopedUserDataRwRef<T>) -> Self {
+        udref.any
+    }
+}
+
+#[cfg(feature = "send")]
+pub struct ScopedUserDataRw<T: UserData>(ScopedUserDataRwRef<T>);
+
+#[cfg(feature = "send")]
+impl<T: UserData> Deref for ScopedUserDataRw<T> {
+    type Target = ScopedUserDataRwRef<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "send")]
+impl<T: UserData> DerefMut for ScopedUserDataRw<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "send")]
+impl<T: UserData> ToLua for &ScopedUserDataRw<T> {
+    fn push_to_stack(self, l: &lua::State) {
+        (&self.0).push_to_stack(l);
+    }
+
+    fn to_value(self, l: &lua::State) -> Value {
+        (&self.0).to_value(l)
+    }
+}
+
+#[cfg(feature = "send")]
+impl<T: UserData> Drop for ScopedUserDataRw<T> {
+    fn drop(&mut self) {
+        super::drop_userdata_at::<T>(self.0.ptr);
+        let _ = self.0.value.write().take();
+    }
+}
+
+#[cfg(feature = "send")]
+impl lua::State {
+    pub fn create_scoped_userdata_rw<T: UserData>(&self, value: T) -> ScopedUserDataRw<T> {
+        let value = Arc::new(xutex::RwLock::new(Some(value)));
+        let (ptr, any) = self.create_userdata_impl::<_, T>(value.clone());
+        ScopedUserDataRw(ScopedUserDataRwRef {
+            ptr: ptr as usize,
+            value,
+            any,
+        })
+    }
+}
+
+#[cfg(feature = "send")]
+impl AnyUserData {
+    #[must_use]
+    #[inline]
+    pub fn scoped_cast_to_rw<T: UserData>(self, l: &lua::State) -> Option<ScopedUserDataRwRef<T>> {
+        if !self.is::<UserDataStorageRw<T>>(l) {
+            return None;
+        }
+        let ptr = self.ptr();
+        // SAFETY: We have checked the type above
+        let storage = unsafe { &*(ptr.cast::<UserDataStorageRw<T>>()) }.clone();
+        if storage.read().is_none() {
+            return None;
+        }
+        Some(ScopedUserDataRwRef {
+            ptr: ptr as usize,
+            value: storage,
+            any: self,
+        })
+    }
+}
@@ -1,8 +1,4 @@
-use std::{
-    collections::{VecDeque, vec_deque},
-    fmt,
-    ops::{Deref, DerefMut},
-};
+use std::fmt;
 
 use crate::lua::{
     self, FromLuaMulti, Result, ToLuaMulti, ffi,
@@ -18,7 +14,7 @@ pub struct Value {
     pub(crate) inner: ValueRef,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ValueKind {
     Nil,
     Bool,
@@ -107,6 +103,25 @@ impl Value {
     pub(crate) fn thread(&self) -> lua::State {
         self.inner.thread()
     }
+
+    /// Compares two values by Lua semantics: numbers/strings compare by value, tables/userdata
+    /// honor an `__eq` metamethod if present, everything else falls back to identity. Mirrors
+    /// Lua's `==` operator, via `lua_equal`.
+    pub fn equals(&self, state: &lua::State, other: &Value) -> bool {
+        let _sg = state.stack_guard();
+        self.push_to_stack(state);
+        other.push_to_stack(state);
+        ffi::lua_equal(state.0, -2, -1)
+    }
+
+    /// Compares two values without invoking any `__eq` metamethod: numbers/strings compare by
+    /// value, tables/userdata/functions/threads compare by identity. Via `lua_rawequal`.
+    pub fn raw_equals(&self, state: &lua::State, other: &Value) -> bool {
+        let _sg = state.stack_guard();
+        self.push_to_stack(state);
+        other.push_to_stack(state);
+        ffi::lua_rawequal(state.0, -2, -1)
+    }
 }
 
 impl fmt::Display for Value {
@@ -145,50 +160,109 @@ impl FromLua for Value {
     }
 }
 
+/// A list of [`Value`]s used for multi-value Lua calls and returns.
+///
+/// Internally this stores its values in a plain `Vec<Value>`, but *backwards*: the logical
+/// front of the multi-value lives at the end of the `Vec`. That makes `push_front`/`pop_front`
+/// (the operations on the hot call path, since arguments/returns are naturally built up front
+/// first) cheap `Vec::push`/`Vec::pop` instead of requiring ring-buffer bookkeeping. The
+/// trade-off falls on `push_back`/`pop_back`, which are rarely used off the hot path. This is
+/// the representation mlua adopted for the same reason.
+///
+/// An earlier, `VecDeque`-backed version of this type implemented `Deref<Target = VecDeque<Value>>`;
+/// that's intentionally gone. The reversed `Vec` layout has no `VecDeque` to deref to, and
+/// re-reversing one just to hand it out would defeat the whole point of the representation. Use
+/// the inherent methods instead ([`Self::front`]/[`Self::back`]/[`Self::len`]/[`Self::is_empty`]/
+/// [`Self::iter`]), all of which present the same logical front-to-back ordering `Deref` did.
 #[derive(Default, Debug, Clone)]
-pub struct MultiValue(VecDeque<Value>);
-
-impl Deref for MultiValue {
-    type Target = VecDeque<Value>;
-
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
-}
-
-impl DerefMut for MultiValue {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
-    }
-}
+pub struct MultiValue(Vec<Value>);
 
 impl MultiValue {
     /// Creates an empty `MultiValue` containing no values.
     #[inline]
     pub const fn new() -> MultiValue {
-        MultiValue(VecDeque::new())
+        MultiValue(Vec::new())
     }
 
     /// Creates an empty `MultiValue` container with space for at least `capacity` elements.
+    #[inline]
     pub fn with_capacity(capacity: usize) -> MultiValue {
-        MultiValue(VecDeque::with_capacity(capacity))
+        MultiValue(Vec::with_capacity(capacity))
     }
 
-    /// Creates a `MultiValue` container from vector of values.
+    /// Creates a `MultiValue` container from a vector of values, in front-to-back order.
     ///
-    /// This method works in *O*(1) time and does not allocate any additional memory.
+    /// This method needs *O*(*n*) data movement to reverse `vec` into the internal backwards
+    /// order.
     #[inline]
-    pub fn from_vec(vec: Vec<Value>) -> MultiValue {
-        vec.into()
+    pub fn from_vec(mut vec: Vec<Value>) -> MultiValue {
+        vec.reverse();
+        MultiValue(vec)
     }
 
-    /// Consumes the `MultiValue` and returns a vector of values.
+    /// Consumes the `MultiValue` and returns a vector of values, in front-to-back order.
     ///
-    /// This method needs *O*(*n*) data movement if the circular buffer doesn't happen to be at the
-    /// beginning of the allocation.
+    /// This method needs *O*(*n*) data movement to reverse the internal backwards order back
+    /// into front-to-back order.
     #[inline]
     pub fn into_vec(self) -> Vec<Value> {
-        self.into()
+        let mut vec = self.0;
+        vec.reverse();
+        vec
+    }
+
+    /// Pushes a value to the front of the list.
+    #[inline]
+    pub fn push_front(&mut self, value: Value) {
+        self.0.push(value);
+    }
+
+    /// Removes and returns the value at the front of the list.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<Value> {
+        self.0.pop()
+    }
+
+    /// Pushes a value to the back of the list.
+    #[inline]
+    pub fn push_back(&mut self, value: Value) {
+        self.0.insert(0, value);
+    }
+
+    /// Removes and returns the value at the back of the list.
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<Value> {
+        (!self.0.is_empty()).then(|| self.0.remove(0))
+    }
+
+    /// Returns a reference to the value at the front of the list.
+    #[inline]
+    pub fn front(&self) -> Option<&Value> {
+        self.0.last()
+    }
+
+    /// Returns a reference to the value at the back of the list.
+    #[inline]
+    pub fn back(&self) -> Option<&Value> {
+        self.0.first()
+    }
+
+    /// Returns the number of values in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the list has no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the values in the list, in front-to-back order.
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Value> {
+        self.0.iter().rev()
     }
 
     #[allow(unused)]
@@ -198,52 +272,50 @@ impl MultiValue {
         iter: impl IntoIterator<Item = T>,
     ) -> Result<Self> {
         let iter = iter.into_iter();
-        let mut multi_value = MultiValue::with_capacity(iter.size_hint().0);
+        let mut vec = Vec::with_capacity(iter.size_hint().0);
         for value in iter {
-            multi_value.push_back(value.to_value(state));
+            vec.push(value.to_value(state));
         }
-        Ok(multi_value)
+        Ok(MultiValue::from_vec(vec))
     }
 }
 
 impl From<Vec<Value>> for MultiValue {
     #[inline]
     fn from(value: Vec<Value>) -> Self {
-        MultiValue(value.into())
+        MultiValue::from_vec(value)
     }
 }
 
 impl From<MultiValue> for Vec<Value> {
     #[inline]
     fn from(value: MultiValue) -> Self {
-        value.0.into()
+        value.into_vec()
     }
 }
 
 impl FromIterator<Value> for MultiValue {
     #[inline]
     fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Self {
-        let mut multi_value = MultiValue::new();
-        multi_value.extend(iter);
-        multi_value
+        MultiValue::from_vec(iter.into_iter().collect())
     }
 }
 
 impl IntoIterator for MultiValue {
     type Item = Value;
-    type IntoIter = vec_deque::IntoIter<Value>;
+    type IntoIter = std::iter::Rev<std::vec::IntoIter<Value>>;
 
     #[inline]
-    fn into_iter(mut self) -> Self::IntoIter {
-        let deque = std::mem::take(&mut self.0);
-        std::mem::forget(self);
-        deque.into_iter()
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter().rev()
     }
 }
 
 impl ToLuaMulti for MultiValue {
     fn push_to_stack_multi(self, state: &lua::State) {
-        for value in self {
+        // `self.0` stores values back-to-front, so iterating it in reverse already yields the
+        // correct front-to-back stack push order without any data movement.
+        for value in self.0.into_iter().rev() {
             value.push_to_stack(state);
         }
     }
@@ -255,10 +327,12 @@ impl FromLuaMulti for MultiValue {
         start_index: i32,
         count: i32,
     ) -> Result<(Self, i32)> {
-        let mut multi_value = MultiValue::with_capacity(count as usize);
-        for i in 0..count {
-            multi_value.push_back(Value::from_stack(state, start_index + i));
+        // Walk the stack from its last argument down to its first, so the values land directly
+        // in the back-to-front internal order without a separate reversal pass.
+        let mut vec = Vec::with_capacity(count as usize);
+        for i in (0..count).rev() {
+            vec.push(Value::from_stack(state, start_index + i));
         }
-        Ok((multi_value, count))
+        Ok((MultiValue(vec), count))
     }
 }
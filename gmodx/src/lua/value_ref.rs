@@ -1,8 +1,40 @@
+//! The reference-thread fast path backing [`Value`](crate::lua::Value)/[`ValueRef`]: instead of
+//! anchoring every value in the registry (a hash table lookup per push/pop), we keep one
+//! dedicated auxiliary Lua thread (`ref_state()`) alive for the whole realm and store values as
+//! plain stack slots on it. Anchoring/releasing a value is then just `lua_xmove`/`lua_xpush` plus
+//! bumping or recycling an index — no `luaL_ref`/`luaL_unref` registry traffic on the hot path at
+//! all, and no fallback to the registry when the slot pools below run out.
+//!
+//! The first [`RESERVED_SLOTS`] indices are pre-pushed at open time and handed out through a
+//! lock-free fixed pool (`RESERVED_FREE`, one bit per slot): acquiring or releasing one of them
+//! is a single CAS/`fetch_or`, not a lock. This covers the common case (a short-lived ref created
+//! and dropped well within the reserved range) without any contention. Only once that pool is
+//! exhausted do we fall back to `OVERFLOW_FREE`, a small sharded free list (still `Mutex`-guarded,
+//! but split across [`OVERFLOW_SHARDS`] independent maps instead of one global lock) plus the
+//! `REF_STACK_TOP` bump counter for indices nobody has freed yet.
+//!
+//! Freed slots (reserved or overflow) aren't nil'd out immediately: a slot can be freed out of
+//! order (the ref thread's stack looks like a dense array, not a LIFO), and naively leaving a
+//! freed slot's old value in place would keep it alive for the GC. The actual `lua_pushnil`/
+//! `lua_replace` cleanup is deferred to the next tick (refs can only be dropped off the main
+//! thread in some paths) via `next_tick`, and must be skipped if the slot was already handed back
+//! out by the time the tick runs:
+//! - Reserved slots guard against that race with a per-slot generation counter
+//!   (`RESERVED_GEN`): freeing bumps it and the scheduled tick only nils if the generation is
+//!   still what it captured, i.e. nobody reclaimed the slot in the meantime.
+//! - Overflow slots use the same "should nil" flag the original single free-list used, just
+//!   sharded: reuse removes the entry outright, so a tick that finds it gone knows to skip it.
+//!
+//! Invariant: a [`Value`](crate::lua::Value)'s underlying `ValueRef` lives in the ref thread, not
+//! in whatever `lua::State` happened to create it — so, unlike a plain stack index, it stays
+//! valid to `push` onto *any* `lua::State` on the same realm (main state or a coroutine) for as
+//! long as that realm stays open, not just the one it was read from.
+
 use std::{
     fmt,
     sync::{
-        Mutex,
-        atomic::{AtomicI32, AtomicPtr, Ordering},
+        LazyLock, Mutex,
+        atomic::{AtomicI32, AtomicPtr, AtomicU16, AtomicU32, Ordering},
     },
 };
 
@@ -14,7 +46,31 @@ use crate::{
     sync::XRc,
 };
 
-static FREE_SLOTS: Mutex<FxHashMap<i32, bool>> = Mutex::new(FxHashMap::with_hasher(FxBuildHasher));
+/// Size of the lock-free fast-path pool. Kept small and fixed (mirrors mlua's reserved-slot
+/// approach) so the common case of a short-lived ref never touches a lock.
+const RESERVED_SLOTS: i32 = 16;
+
+/// Bit `i` set means reserved slot `i + 1` is free. Acquiring/releasing a reserved slot is a
+/// single CAS (acquire) / `fetch_or` (release) against this mask — no lock involved.
+static RESERVED_FREE: AtomicU16 = AtomicU16::new(u16::MAX);
+
+/// Per-reserved-slot generation counter, bumped on every acquire *and* every release. A deferred
+/// nil scheduled by [`ValueRef::drop`] captures the generation at release time; if it still
+/// matches when the tick runs, nobody has reclaimed the slot since, so it's safe to nil.
+static RESERVED_GEN: LazyLock<[AtomicU32; RESERVED_SLOTS as usize]> =
+    LazyLock::new(|| std::array::from_fn(|_| AtomicU32::new(0)));
+
+/// Overflow free list, sharded to spread out the lock contention a single global map would cause
+/// once the reserved pool is exhausted (expected to be rare — bursty allocation beyond
+/// [`RESERVED_SLOTS`] concurrently live refs).
+const OVERFLOW_SHARDS: usize = 4;
+static OVERFLOW_FREE: [Mutex<FxHashMap<i32, bool>>; OVERFLOW_SHARDS] = [
+    Mutex::new(FxHashMap::with_hasher(FxBuildHasher)),
+    Mutex::new(FxHashMap::with_hasher(FxBuildHasher)),
+    Mutex::new(FxHashMap::with_hasher(FxBuildHasher)),
+    Mutex::new(FxHashMap::with_hasher(FxBuildHasher)),
+];
+
 static REF_STATE: AtomicPtr<ffi::lua_State> = AtomicPtr::new(std::ptr::null_mut());
 static REF_STACK_TOP: AtomicI32 = AtomicI32::new(0);
 
@@ -37,21 +93,67 @@ fn ref_state() -> lua::State {
     lua::State(ptr)
 }
 
+#[inline]
+fn overflow_shard(index: i32) -> &'static Mutex<FxHashMap<i32, bool>> {
+    &OVERFLOW_FREE[index as usize % OVERFLOW_SHARDS]
+}
+
+/// Tries to lock-free-grab a slot from the reserved pool, bumping its generation so any
+/// previously-scheduled deferred nil for it (from before it was last freed) is now stale.
+fn try_pop_reserved() -> Option<i32> {
+    loop {
+        let bits = RESERVED_FREE.load(Ordering::Acquire);
+        if bits == 0 {
+            return None;
+        }
+        let bit = bits.trailing_zeros();
+        let new_bits = bits & !(1 << bit);
+        if RESERVED_FREE
+            .compare_exchange_weak(bits, new_bits, Ordering::AcqRel, Ordering::Relaxed)
+            .is_ok()
+        {
+            RESERVED_GEN[bit as usize].fetch_add(1, Ordering::AcqRel);
+            return Some(bit as i32 + 1);
+        }
+    }
+}
+
+/// Marks a reserved slot free again, returning the generation a deferred nil should compare
+/// against before touching the slot.
+fn release_reserved(index: i32) -> u32 {
+    let bit = (index - 1) as usize;
+    let gen = RESERVED_GEN[bit].fetch_add(1, Ordering::AcqRel) + 1;
+    RESERVED_FREE.fetch_or(1 << bit, Ordering::AcqRel);
+    gen
+}
+
 fn stack_pop() -> i32 {
     let state = ref_state();
-    let free = {
-        let mut free_slots = FREE_SLOTS.lock().unwrap();
-        free_slots.iter().next().map(|(&v, _)| v).map(|v| {
-            free_slots.remove(&v);
-            v
-        })
-    };
-    if let Some(free) = free {
-        ffi::lua_replace(state.0, free);
-        free
-    } else {
-        REF_STACK_TOP.fetch_add(1, Ordering::AcqRel) + 1
+
+    // Recycled slots (reserved or overflow) already hold stale contents, so the freshly
+    // `lua_xmove`'d value sitting on top of the stack needs replacing into them. A freshly
+    // bumped index, on the other hand, *is* the current top (nothing to replace from), so it's
+    // returned as-is.
+    if let Some(index) = try_pop_reserved() {
+        ffi::lua_replace(state.0, index);
+        return index;
+    }
+
+    for shard in &OVERFLOW_FREE {
+        let free = {
+            let mut free_slots = shard.lock().unwrap();
+            free_slots.iter().next().map(|(&v, _)| v).map(|v| {
+                free_slots.remove(&v);
+                v
+            })
+        };
+        if let Some(free) = free {
+            ffi::lua_replace(state.0, free);
+            return free;
+        }
     }
+
+    REF_STACK_TOP.fetch_add(1, Ordering::AcqRel) + 1
 }
 
 impl ValueRef {
@@ -64,7 +166,16 @@ impl ValueRef {
     }
 
     pub(crate) fn push(&self, to: &lua::State) {
-        ffi::lua_xpush(ref_state().0, to.0, self.index);
+        let thread = ref_state();
+        if to.0 == thread.0 {
+            // `lua_xmove`/`lua_xpush` move a value *between* two different stacks; pushing a
+            // duplicate of a value onto the very thread it already lives on (e.g. a raw table op
+            // working entirely on the ref thread, see `Table::raw_get`/`raw_set`) is just a
+            // same-thread `lua_pushvalue` instead.
+            ffi::lua_pushvalue(to.0, self.index);
+        } else {
+            ffi::lua_xpush(thread.0, to.0, self.index);
+        }
     }
 
     pub(crate) fn pop() -> Self {
@@ -90,23 +201,41 @@ impl Drop for ValueRef {
             && XRc::into_inner(xrc).is_some()
         {
             let index = self.index;
-            FREE_SLOTS.lock().unwrap().insert(index, true);
-            // Make sure we only access the ref_thread on the main thread.
-            next_tick(move |_| {
-                let state = ref_state().0;
-                debug_assert!(
-                    ffi::lua_gettop(state) >= index,
-                    "GC finalizer is not allowed in ref_thread"
-                );
-                let mut free_slots = FREE_SLOTS.lock().unwrap();
-                if let Some(&should_nil) = free_slots.get(&index)
-                    && should_nil
-                {
-                    ffi::lua_pushnil(state);
-                    ffi::lua_replace(state, index);
-                    free_slots.insert(index, false);
-                }
-            });
+
+            if index <= RESERVED_SLOTS {
+                let bit = (index - 1) as usize;
+                let expected_gen = release_reserved(index);
+                // Make sure we only access the ref_thread on the main thread.
+                next_tick(move |_| {
+                    let state = ref_state().0;
+                    debug_assert!(
+                        ffi::lua_gettop(state) >= index,
+                        "GC finalizer is not allowed in ref_thread"
+                    );
+                    if RESERVED_GEN[bit].load(Ordering::Acquire) == expected_gen {
+                        ffi::lua_pushnil(state);
+                        ffi::lua_replace(state, index);
+                    }
+                });
+            } else {
+                overflow_shard(index).lock().unwrap().insert(index, true);
+                // Make sure we only access the ref_thread on the main thread.
+                next_tick(move |_| {
+                    let state = ref_state().0;
+                    debug_assert!(
+                        ffi::lua_gettop(state) >= index,
+                        "GC finalizer is not allowed in ref_thread"
+                    );
+                    let mut free_slots = overflow_shard(index).lock().unwrap();
+                    if let Some(&should_nil) = free_slots.get(&index)
+                        && should_nil
+                    {
+                        ffi::lua_pushnil(state);
+                        ffi::lua_replace(state, index);
+                        free_slots.insert(index, false);
+                    }
+                });
+            }
         }
     }
 }
@@ -117,6 +246,22 @@ impl fmt::Debug for ValueRef {
     }
 }
 
+/// Exposes the lock-free reserved-slot pool to `benches/value_ref_contention.rs`. The allocator
+/// itself never touches Lua (it's pure atomics over [`RESERVED_FREE`]/[`RESERVED_GEN`]), so it can
+/// be driven directly without a real `lua_State` standing in for the ref thread. Dev-only: gated
+/// behind a feature so none of this ships in a normal build.
+#[cfg(feature = "internal-benchmarks")]
+#[doc(hidden)]
+pub mod bench_support {
+    pub fn acquire_reserved() -> Option<i32> {
+        super::try_pop_reserved()
+    }
+
+    pub fn release_reserved(index: i32) {
+        super::release_reserved(index);
+    }
+}
+
 inventory::submit! {
     crate::open_close::new(
         0,
@@ -125,12 +270,25 @@ inventory::submit! {
             let thread = ffi::new_thread(l.0);
             // leak the reference thread so it doesn't get GC'd
             ffi::luaL_ref(l.0, ffi::LUA_REGISTRYINDEX);
-            REF_STACK_TOP.store(0, Ordering::Release);
+
+            // Pre-push the reserved pool's slots so they physically exist on the ref thread's
+            // stack up front, and reset the lock-free allocator state over them.
+            for _ in 0..RESERVED_SLOTS {
+                ffi::lua_pushnil(thread);
+            }
+            RESERVED_FREE.store(u16::MAX, Ordering::Release);
+            for generation in RESERVED_GEN.iter() {
+                generation.store(0, Ordering::Release);
+            }
+
+            REF_STACK_TOP.store(RESERVED_SLOTS, Ordering::Release);
             REF_STATE.store(thread, Ordering::Release);
         },
         |_| {
             REF_STATE.store(std::ptr::null_mut(), Ordering::Release);
-            FREE_SLOTS.lock().unwrap().clear();
+            for shard in &OVERFLOW_FREE {
+                shard.lock().unwrap().clear();
+            }
         },
     )
 }
@@ -61,6 +61,17 @@ macro_rules! gmodx_panic {
     };
 }
 
+/// Runs `$body` (a closure over the raw `*mut ffi::lua_State`) through a protected trampoline via
+/// [`lua::State::protect_lua_stack`], instead of hand-writing an `extern "C-unwind"` trampoline
+/// function and its stack plumbing for every metamethod-triggering operation. `$nargs` values
+/// must already be on top of `$state`'s stack; `$body` is trusted to leave exactly `$nresults` on
+/// it when it returns, same as the `nresults` of a direct Lua call.
+macro_rules! protect_lua {
+    ($state:expr, $nargs:expr, $nresults:expr, $body:expr) => {
+        $state.protect_lua_stack($nargs, $nresults, $body)
+    };
+}
+
 macro_rules! gmodx_debug_assert {
     ($cond:expr, $msg:expr) => {
         debug_assert!($cond, bug_msg!($msg));
@@ -1,3 +1,6 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
 use std::thread;
 
 use crate::is_main_thread;
@@ -54,6 +57,59 @@ where
     }
 }
 
+struct AsyncNextTickState<R> {
+    result: Option<R>,
+    waker: Option<Waker>,
+}
+
+/// The [`Future`] returned by [`async_next_tick`].
+pub struct AsyncNextTick<R> {
+    state: Arc<Mutex<AsyncNextTickState<R>>>,
+}
+
+impl<R> Future for AsyncNextTick<R> {
+    type Output = R;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<R> {
+        let mut state = self.state.lock().unwrap();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Like [`next_tick`], but returns a future that resolves with `f`'s return value
+/// instead of taking a callback. Lets code running off the main thread (e.g. a
+/// tokio task) `.await` a hop onto the main thread and back.
+pub fn async_next_tick<F, R>(f: F) -> AsyncNextTick<R>
+where
+    F: FnOnce(&State) -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let state = Arc::new(Mutex::new(AsyncNextTickState {
+        result: None,
+        waker: None,
+    }));
+    let state2 = state.clone();
+
+    next_tick(move |l| {
+        let waker = {
+            let mut state = state2.lock().unwrap();
+            state.result = Some(f(l));
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    });
+
+    AsyncNextTick { state }
+}
+
 inventory::submit! {
     crate::open_close::new(
         2,
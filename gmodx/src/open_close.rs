@@ -1,4 +1,7 @@
-use std::{collections::HashSet, sync::atomic::AtomicBool};
+use std::{
+    collections::HashSet,
+    sync::atomic::{AtomicBool, AtomicU8},
+};
 
 use crate::lua::{self, ffi};
 
@@ -22,11 +25,86 @@ pub fn is_open() -> bool {
     !is_closed()
 }
 
+/// Which Lua realm the game state is running in. Detected once, during [`load_all`], from the
+/// `CLIENT`/`SERVER`/`MENU` globals GMod defines in every realm (mirroring how the `gmod` crate
+/// derives `is_client`/`is_server`/`is_menu`), and cached for the rest of the process's life.
+/// Query it via [`current_realm`], or [`lua::State::realm`] for call-chaining off a state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Realm {
+    Client,
+    Server,
+    Menu,
+}
+
+impl Realm {
+    const fn bit(self) -> u8 {
+        match self {
+            Realm::Client => 1 << 0,
+            Realm::Server => 1 << 1,
+            Realm::Menu => 1 << 2,
+        }
+    }
+}
+
+impl std::ops::BitOr for Realm {
+    type Output = u8;
+    fn bitor(self, rhs: Realm) -> u8 {
+        self.bit() | rhs.bit()
+    }
+}
+
+impl std::ops::BitOr<u8> for Realm {
+    type Output = u8;
+    fn bitor(self, rhs: u8) -> u8 {
+        self.bit() | rhs
+    }
+}
+
+/// Bitmask covering all three realms: the default for an [`OpenClose`] registration that doesn't
+/// care which realm it loads in.
+pub const ALL_REALMS: u8 = Realm::Client.bit() | Realm::Server.bit() | Realm::Menu.bit();
+
+static DETECTED_REALM: AtomicU8 = AtomicU8::new(0);
+
+/// The realm detected by [`load_all`]. Panics if called before `load_all` has run.
+pub fn current_realm() -> Realm {
+    match DETECTED_REALM.load(std::sync::atomic::Ordering::Acquire) {
+        0 => panic!("realm has not been detected yet (load_all hasn't run)"),
+        bits if bits == Realm::Server.bit() => Realm::Server,
+        bits if bits == Realm::Menu.bit() => Realm::Menu,
+        bits if bits == Realm::Client.bit() => Realm::Client,
+        bits => unreachable!("invalid cached realm bits: {bits}"),
+    }
+}
+
+fn detect_realm(state: &lua::State) {
+    // SERVER/MENU are checked explicitly and CLIENT is the fallback, since regular game clients
+    // only ever define CLIENT, while the menu state defines both CLIENT and MENU.
+    let realm = if state.get_global::<bool>("SERVER").unwrap_or(false) {
+        Realm::Server
+    } else if state.get_global::<bool>("MENU").unwrap_or(false) {
+        Realm::Menu
+    } else {
+        Realm::Client
+    };
+    DETECTED_REALM.store(realm.bit(), std::sync::atomic::Ordering::Release);
+}
+
+impl lua::State {
+    /// The Lua realm this state is running in. See [`current_realm`].
+    pub fn realm(&self) -> Realm {
+        current_realm()
+    }
+}
+
 pub struct OpenClose {
     pub priority: i32, // Lower priority loads first
     pub id: &'static str,
     pub open: fn(&lua::State),
     pub close: fn(&lua::State),
+    /// Bitmask (OR [`Realm`] values together, or use [`ALL_REALMS`]) of which realms this module
+    /// should load in. `load_all`/`unload_all` skip it outside of them.
+    pub realms: u8,
 }
 
 pub const fn new(
@@ -34,12 +112,25 @@ pub const fn new(
     id: &'static str,
     open: fn(&lua::State),
     close: fn(&lua::State),
+) -> OpenClose {
+    new_for_realms(priority, id, open, close, ALL_REALMS)
+}
+
+/// Like [`new`], but restricted to the given realm bitmask (e.g. `Realm::Client | Realm::Menu`)
+/// instead of loading in all of them.
+pub const fn new_for_realms(
+    priority: i32,
+    id: &'static str,
+    open: fn(&lua::State),
+    close: fn(&lua::State),
+    realms: u8,
 ) -> OpenClose {
     OpenClose {
         priority,
         id,
         open,
         close,
+        realms,
     }
 }
 
@@ -59,6 +150,9 @@ fn get_sorted_modules() -> Vec<&'static OpenClose> {
     // For modules with same priority, maintain a stable order
     modules.sort_by_key(|m| (m.priority, m.id));
 
+    let current = current_realm().bit();
+    modules.retain(|m| m.realms & current != 0);
+
     modules
 }
 
@@ -67,6 +161,8 @@ pub fn load_all(state: &lua::State) {
     IS_MAIN_THREAD.with(|cell| cell.set(true));
     GMOD_CLOSED.store(false, std::sync::atomic::Ordering::Release);
 
+    detect_realm(state);
+
     let modules = get_sorted_modules();
     for module in &modules {
         ffi::lua_settop(state.0, 1); // Clear the stack, on gmod13_open, there is a string at index 1
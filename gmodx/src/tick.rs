@@ -1,6 +1,9 @@
 use std::collections::VecDeque;
-use std::sync::Mutex;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll, Wake, Waker};
 
 use std::time::{Duration, Instant};
 
@@ -25,6 +28,10 @@ static TASKS: Mutex<Vec<Box<dyn FnOnce() + Send>>> = Mutex::new(Vec::new());
 
 static TASK_COUNT: AtomicUsize = AtomicUsize::new(0);
 
+// Futures woken since the last tick, waiting to be polled again on the next one.
+static PENDING_WAKES: Mutex<Vec<Arc<TaskSlot>>> = Mutex::new(Vec::new());
+static PENDING_WAKE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
 struct OneShotHooks {
     tick_rate: f64,
     hooks: VecDeque<Task>,
@@ -75,6 +82,116 @@ pub fn next_tick(f: impl FnOnce(&lua::State) + Send + 'static) {
     TASK_COUNT.fetch_add(1, Ordering::Relaxed);
 }
 
+struct TaskSlot {
+    future: Mutex<Option<Pin<Box<dyn Future<Output = ()> + Send>>>>,
+}
+
+struct TaskWaker(Arc<TaskSlot>);
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        schedule(self.0.clone());
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        schedule(self.0.clone());
+    }
+}
+
+// Queues `slot` to be polled again once the next tick's one-shot budget opens up.
+fn schedule(slot: Arc<TaskSlot>) {
+    PENDING_WAKES.lock().unwrap().push(slot);
+    PENDING_WAKE_COUNT.fetch_add(1, Ordering::Relaxed);
+}
+
+fn poll_task(_l: &lua::State, slot: Arc<TaskSlot>) {
+    let mut guard = slot.future.lock().unwrap();
+    let Some(future) = guard.as_mut() else {
+        return; // already finished, e.g. a stale wake that raced completion
+    };
+
+    let waker = Waker::from(Arc::new(TaskWaker(slot.clone())));
+    let mut cx = Context::from_waker(&waker);
+
+    if future.as_mut().poll(&mut cx).is_ready() {
+        *guard = None;
+    }
+}
+
+/// Spawn a future to run cooperatively alongside the Lua game loop.
+///
+/// The future is polled on the main thread during [`run_tick_hooks`]'s one-shot budget, so it
+/// must not block; use [`sleep`] or [`yield_now`] (or await futures backed by other gmodx
+/// executors) instead of blocking calls.
+///
+/// Example:
+/// ```
+/// gmodx::tick::spawn(async {
+///     gmodx::tick::sleep(std::time::Duration::from_secs(1)).await;
+///     println!("a second has passed!");
+/// });
+/// ```
+#[inline(never)]
+pub fn spawn(future: impl Future<Output = ()> + Send + 'static) {
+    let slot = Arc::new(TaskSlot {
+        future: Mutex::new(Some(Box::pin(future))),
+    });
+    schedule(slot);
+}
+
+/// A future that resolves once `duration` has elapsed, rechecked once per tick.
+pub struct Sleep {
+    deadline: Instant,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            Poll::Ready(())
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Returns a future that resolves once `duration` has elapsed, for use inside a
+/// [`spawn`]ed future.
+#[inline(never)]
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        deadline: Instant::now() + duration,
+    }
+}
+
+/// A future that suspends the current task until the next tick.
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            Poll::Ready(())
+        } else {
+            self.yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Returns a future that suspends the current task until the next tick, for use inside a
+/// [`spawn`]ed future.
+#[inline(never)]
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
 #[inline(never)]
 pub fn flush_next_tick(l: &lua::State) {
     let mut deadline = Instant::now();
@@ -108,6 +225,16 @@ fn run_tick_hooks(l: &lua::State) {
         }
     }
 
+    // move any futures woken since the last tick into this tick's one-shot queue
+    if PENDING_WAKE_COUNT.load(Ordering::Relaxed) > 0 {
+        let slots = std::mem::take(&mut *PENDING_WAKES.lock().unwrap());
+        let mut oneshot = ONESHOT_HOOKS.lock().unwrap();
+        for slot in slots {
+            PENDING_WAKE_COUNT.fetch_sub(1, Ordering::Relaxed);
+            oneshot.hooks.push_back(Box::new(move |l| poll_task(l, slot)));
+        }
+    }
+
     // run persistent hooks
     HOOKS.lock().unwrap().retain(|f| !f(l));
 
@@ -122,6 +249,8 @@ inventory::submit! {
             HOOKS.lock().unwrap().clear();
             ONESHOT_HOOKS.lock().unwrap().hooks.clear();
             TASKS.lock().unwrap().clear();
+            PENDING_WAKES.lock().unwrap().clear();
+            PENDING_WAKE_COUNT.store(0, Ordering::Relaxed);
 
             if let Ok(tick_interval) = l
                 .get_global::<Table>("engine")